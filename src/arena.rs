@@ -0,0 +1,443 @@
+use std::borrow::Borrow;
+use std::io::{self, Write};
+
+use bumpalo::Bump;
+
+use crate::{
+    array::Array,
+    base::{
+        TypedCustomValue, TypedValue, ValueAsContainer, ValueAsMutContainer, ValueAsScalar,
+        ValueIntoContainer, ValueIntoString, Writable,
+    },
+    generator::{
+        BaseGenerator, DumpGenerator, Node, PrettyGenerator, PrettyWriterGenerator, WriterGenerator,
+    },
+    object::Object,
+    StaticNode, ValueType,
+};
+
+/// An array allocated in an arena
+pub type ArenaVec<'bump, T> = bumpalo::collections::Vec<'bump, T>;
+
+/// A map allocated in an arena
+pub type ArenaMap<'bump, K, V> =
+    hashbrown::HashMap<K, V, hashbrown::hash_map::DefaultHashBuilder, &'bump Bump>;
+
+/// A value where strings, arrays and objects live in a `bumpalo` arena, the
+/// whole tree is freed at once when the arena is reset or dropped
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaValue<'bump> {
+    /// Null, bools and numbers
+    Static(StaticNode),
+    /// A string
+    String(&'bump str),
+    /// An array
+    Array(ArenaVec<'bump, ArenaValue<'bump>>),
+    /// An object
+    Object(ArenaMap<'bump, &'bump str, ArenaValue<'bump>>),
+}
+
+/// Creates values in an arena
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaBuilder<'bump> {
+    bump: &'bump Bump,
+}
+
+impl<'bump> ArenaBuilder<'bump> {
+    /// Creates a builder for the arena `bump`
+    #[must_use]
+    pub fn new(bump: &'bump Bump) -> Self {
+        Self { bump }
+    }
+
+    /// The arena values are allocated in
+    #[must_use]
+    pub fn bump(&self) -> &'bump Bump {
+        self.bump
+    }
+
+    /// Copies a string into the arena, use it for object keys
+    #[must_use]
+    pub fn str(&self, s: &str) -> &'bump str {
+        self.bump.alloc_str(s)
+    }
+
+    /// Returns a string value copied into the arena
+    #[must_use]
+    pub fn string(&self, s: &str) -> ArenaValue<'bump> {
+        ArenaValue::String(self.str(s))
+    }
+
+    /// Returns an empty array with a given capacity
+    #[must_use]
+    pub fn array_with_capacity(&self, capacity: usize) -> ArenaValue<'bump> {
+        ArenaValue::Array(ArenaVec::with_capacity_in(capacity, self.bump))
+    }
+
+    /// Returns an empty object with a given capacity
+    #[must_use]
+    pub fn object_with_capacity(&self, capacity: usize) -> ArenaValue<'bump> {
+        ArenaValue::Object(ArenaMap::with_capacity_in(capacity, self.bump))
+    }
+
+    /// Returns an empty array
+    #[must_use]
+    pub fn array(&self) -> ArenaValue<'bump> {
+        self.array_with_capacity(0)
+    }
+
+    /// Returns an empty object
+    #[must_use]
+    pub fn object(&self) -> ArenaValue<'bump> {
+        self.object_with_capacity(0)
+    }
+
+    /// Returns a null value
+    #[must_use]
+    pub fn null(&self) -> ArenaValue<'bump> {
+        ArenaValue::Static(StaticNode::Null)
+    }
+
+    /// Copies any value into the arena
+    #[must_use]
+    pub fn copy_of<V>(&self, value: &V) -> ArenaValue<'bump>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        match Node::of(value) {
+            Node::Null => self.null(),
+            Node::Bool(b) => ArenaValue::from(b),
+            Node::I64(n) => ArenaValue::from(n),
+            Node::U64(n) => ArenaValue::from(n),
+            #[cfg(feature = "128bit")]
+            Node::I128(n) => ArenaValue::Static(StaticNode::I128(n)),
+            #[cfg(feature = "128bit")]
+            Node::U128(n) => ArenaValue::Static(StaticNode::U128(n)),
+            // without 128 bit support large integers are kept as floats
+            #[cfg(not(feature = "128bit"))]
+            #[allow(clippy::cast_precision_loss)]
+            Node::I128(n) => i64::try_from(n).map_or(ArenaValue::from(n as f64), ArenaValue::from),
+            #[cfg(not(feature = "128bit"))]
+            #[allow(clippy::cast_precision_loss)]
+            Node::U128(n) => u64::try_from(n).map_or(ArenaValue::from(n as f64), ArenaValue::from),
+            Node::F64(n) => ArenaValue::from(n),
+            Node::String(s) => self.string(s),
+            Node::Array(array) => {
+                let mut items = ArenaVec::with_capacity_in(array.len(), self.bump);
+                items.extend(array.iter().map(|v| self.copy_of(v)));
+                ArenaValue::Array(items)
+            }
+            Node::Object(object) => {
+                let mut map = ArenaMap::with_capacity_in(object.len(), self.bump);
+                for (k, v) in object.iter() {
+                    map.insert(self.str(k.borrow()), self.copy_of(v));
+                }
+                ArenaValue::Object(map)
+            }
+        }
+    }
+}
+
+impl From<StaticNode> for ArenaValue<'_> {
+    #[inline]
+    fn from(s: StaticNode) -> Self {
+        Self::Static(s)
+    }
+}
+
+impl<'bump> From<&'bump str> for ArenaValue<'bump> {
+    #[inline]
+    fn from(s: &'bump str) -> Self {
+        Self::String(s)
+    }
+}
+
+macro_rules! from_static {
+    ($($t:ty),*) => {
+        $(
+            impl<'bump> From<$t> for ArenaValue<'bump> {
+                #[inline]
+                fn from(v: $t) -> Self {
+                    Self::Static(StaticNode::from(v))
+                }
+            }
+        )*
+    };
+}
+
+from_static!(bool, (), i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+impl TypedValue for ArenaValue<'_> {
+    #[inline]
+    fn value_type(&self) -> ValueType {
+        match self {
+            Self::Static(s) => s.value_type(),
+            Self::String(_) => ValueType::String,
+            Self::Array(_) => ValueType::Array,
+            Self::Object(_) => ValueType::Object,
+        }
+    }
+}
+
+impl TypedCustomValue for ArenaValue<'_> {}
+
+impl ValueAsScalar for ArenaValue<'_> {
+    #[inline]
+    fn as_null(&self) -> Option<()> {
+        self.as_static()?.as_null()
+    }
+
+    #[inline]
+    fn as_bool(&self) -> Option<bool> {
+        self.as_static()?.as_bool()
+    }
+
+    #[inline]
+    fn as_i128(&self) -> Option<i128> {
+        self.as_static()?.as_i128()
+    }
+
+    #[inline]
+    fn as_i64(&self) -> Option<i64> {
+        self.as_static()?.as_i64()
+    }
+
+    #[inline]
+    fn as_u128(&self) -> Option<u128> {
+        self.as_static()?.as_u128()
+    }
+
+    #[inline]
+    fn as_u64(&self) -> Option<u64> {
+        self.as_static()?.as_u64()
+    }
+
+    #[inline]
+    fn as_f64(&self) -> Option<f64> {
+        self.as_static()?.as_f64()
+    }
+
+    #[inline]
+    fn cast_f64(&self) -> Option<f64> {
+        self.as_static()?.cast_f64()
+    }
+
+    #[inline]
+    fn as_str(&self) -> Option<&str> {
+        if let Self::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl ArenaValue<'_> {
+    fn as_static(&self) -> Option<&StaticNode> {
+        if let Self::Static(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'bump> ValueAsContainer for ArenaValue<'bump> {
+    type Array = ArenaVec<'bump, Self>;
+    type Object = ArenaMap<'bump, &'bump str, Self>;
+
+    #[inline]
+    fn as_array(&self) -> Option<&ArenaVec<'bump, Self>> {
+        if let Self::Array(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn as_object(&self) -> Option<&ArenaMap<'bump, &'bump str, Self>> {
+        if let Self::Object(o) = self {
+            Some(o)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'bump> ValueAsMutContainer for ArenaValue<'bump> {
+    type Array = ArenaVec<'bump, Self>;
+    type Object = ArenaMap<'bump, &'bump str, Self>;
+
+    #[inline]
+    fn as_array_mut(&mut self) -> Option<&mut ArenaVec<'bump, Self>> {
+        if let Self::Array(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn as_object_mut(&mut self) -> Option<&mut ArenaMap<'bump, &'bump str, Self>> {
+        if let Self::Object(o) = self {
+            Some(o)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'bump> ValueIntoString for ArenaValue<'bump> {
+    type String = &'bump str;
+
+    #[inline]
+    fn into_string(self) -> Option<&'bump str> {
+        if let Self::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'bump> ValueIntoContainer for ArenaValue<'bump> {
+    type Array = ArenaVec<'bump, Self>;
+    type Object = ArenaMap<'bump, &'bump str, Self>;
+
+    #[inline]
+    fn into_array(self) -> Option<ArenaVec<'bump, Self>> {
+        if let Self::Array(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn into_object(self) -> Option<ArenaMap<'bump, &'bump str, Self>> {
+        if let Self::Object(o) = self {
+            Some(o)
+        } else {
+            None
+        }
+    }
+}
+
+impl Writable for ArenaValue<'_> {
+    fn encode(&self) -> String {
+        let mut g = DumpGenerator::new();
+        // writing to a String can not fail
+        let _: io::Result<()> = g.write_json(self);
+        g.consume()
+    }
+
+    fn encode_pp(&self) -> String {
+        let mut g = PrettyGenerator::new(2);
+        let _: io::Result<()> = g.write_json(self);
+        g.consume()
+    }
+
+    fn write<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        WriterGenerator::new(w).write_json(self)
+    }
+
+    fn write_pp<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        PrettyWriterGenerator::new(w, 2).write_json(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArenaBuilder, ArenaValue};
+    use crate::generator::{encoded_len, encoded_len_pretty};
+    use crate::parser::parse;
+    use crate::prelude::*;
+    use crate::{NativeValue, Value};
+    use bumpalo::Bump;
+    use std::collections::HashMap;
+
+    fn document() -> NativeValue {
+        let mut object = HashMap::new();
+        object.insert("name".to_string(), NativeValue::from("snow ☃"));
+        object.insert("tags".to_string(), NativeValue::from(vec!["a", "b"]));
+        NativeValue::from(vec![
+            NativeValue::from(object),
+            NativeValue::null(),
+            NativeValue::from(-1_i64),
+            NativeValue::from(2.5),
+        ])
+    }
+
+    fn len<V: Value>(value: &V) -> usize {
+        encoded_len(value)
+    }
+
+    #[test]
+    fn copy_of() {
+        let bump = Bump::new();
+        let b = ArenaBuilder::new(&bump);
+        let source = document();
+        let value = b.copy_of(&source);
+        assert!(bump.allocated_bytes() > 0);
+        // the maps may iterate in a different order, so compare parsed values
+        assert_eq!(parse::<NativeValue>(&value.encode()), Ok(source.clone()));
+        assert_eq!(parse::<NativeValue>(&value.encode_pp()), Ok(source.clone()));
+        assert_eq!(len(&value), source.encode().len());
+        assert_eq!(encoded_len_pretty(&value, 2), source.encode_pp().len());
+
+        let object = value.get_idx(0).expect("object");
+        assert_eq!(object.get_str("name"), Some("snow ☃"));
+        assert_eq!(
+            object
+                .get("tags")
+                .and_then(|t| t.get_idx(1))
+                .and_then(ValueAsScalar::as_str),
+            Some("b")
+        );
+        assert_eq!(value.get_idx(2).and_then(ValueAsScalar::as_i64), Some(-1));
+        assert!(value.get_idx(1).map_or(false, TypedScalarValue::is_null));
+    }
+
+    #[test]
+    fn build() {
+        let bump = Bump::new();
+        let b = ArenaBuilder::new(&bump);
+        let mut value = b.object();
+        let mut list = b.array_with_capacity(2);
+        if let Some(a) = list.as_array_mut() {
+            a.push(ArenaValue::from(1_u64));
+            a.push(b.string("two"));
+        }
+        if let Some(o) = value.as_object_mut() {
+            assert_eq!(o.insert(b.str("list"), list), None);
+            assert_eq!(o.insert(b.str("none"), b.null()), None);
+            assert!(o.remove("none").is_some());
+        }
+        assert_eq!(value.encode(), r#"{"list":[1,"two"]}"#);
+        let copy = value.clone();
+        assert_eq!(copy, value);
+        assert_eq!(value.into_object().map(|o| o.len()), Some(1));
+    }
+
+    #[test]
+    fn writers() {
+        let bump = Bump::new();
+        let value = ArenaBuilder::new(&bump).copy_of(&document());
+        let mut out = Vec::new();
+        value.write(&mut out).expect("write to vec");
+        assert_eq!(out, value.encode().as_bytes());
+        let mut out = Vec::new();
+        value.write_pp(&mut out).expect("write to vec");
+        assert_eq!(out, value.encode_pp().as_bytes());
+    }
+}
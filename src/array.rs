@@ -1,9 +1,16 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::slice::SliceIndex;
+use std::sync::Arc;
 
 /// A trait for the minimal common functionality of a vale array
 pub trait Array {
     /// Elements of the array
     type Element;
+    /// Iterator over the elements of the array
+    type Iter<'i>: Iterator<Item = &'i Self::Element>
+    where
+        Self: 'i;
 
     /// Gets a ref to a value based on n index, returns `None` if the
     /// current Value isn't an Array or doesn't contain the index
@@ -15,7 +22,7 @@ pub trait Array {
 
     /// Iterates over the values paris
     #[must_use]
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Element> + 'i>;
+    fn iter(&self) -> Self::Iter<'_>;
 
     /// Number of key/value pairs
     #[must_use]
@@ -33,6 +40,10 @@ pub trait Array {
 pub trait ArrayMut {
     /// Elements of the array
     type Element;
+    /// Iterator over mutable references to the elements of the array
+    type IterMut<'i>: Iterator<Item = &'i mut Self::Element>
+    where
+        Self: 'i;
 
     /// Gets a ref to a value based on n index, returns `None` if the
     /// current Value isn't an Array or doesn't contain the index
@@ -46,10 +57,77 @@ pub trait ArrayMut {
 
     /// Appends e to the end of the `Array`
     fn push(&mut self, e: Self::Element);
+
+    /// Appends e to the end of the `Array`, arrays with a fixed capacity
+    /// hand e back if they are full
+    ///
+    /// # Errors
+    ///
+    /// if the array is full
+    fn try_push(&mut self, e: Self::Element) -> Result<(), Self::Element> {
+        self.push(e);
+        Ok(())
+    }
+
+    /// Inserts e at position `index`, shifting all elements after it to
+    /// the right
+    ///
+    /// # Panics
+    ///
+    /// if `index > len`
+    fn insert(&mut self, index: usize, e: Self::Element);
+
+    /// Removes and returns the element at position `index`, shifting all
+    /// elements after it to the left, returns `None` if the index is out
+    /// of bounds
+    #[must_use]
+    fn remove(&mut self, index: usize) -> Option<Self::Element>;
+
+    /// Removes and returns the element at position `index`, replacing it
+    /// with the last element, returns `None` if the index is out of bounds
+    #[must_use]
+    fn swap_remove(&mut self, index: usize) -> Option<Self::Element>;
+
+    /// Shortens the array to `len` elements, does nothing if the array is
+    /// already shorter
+    fn truncate(&mut self, len: usize);
+
+    /// Removes all elements
+    fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Appends all elements of `iter` to the end of the `Array`
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Self::Element>,
+    {
+        for e in iter {
+            self.push(e);
+        }
+    }
+
+    /// Retains only the elements for which `f` returns `true`
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Element) -> bool;
+
+    /// Sorts the array with the comparator `compare`, the sort is stable
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&Self::Element, &Self::Element) -> Ordering;
+
+    /// Iterates over mutable references to the elements
+    #[must_use]
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
 }
 
 impl<T> Array for Vec<T> {
     type Element = T;
+    type Iter<'i>
+        = std::slice::Iter<'i, T>
+    where
+        Self: 'i;
     #[inline]
     fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
     where
@@ -58,8 +136,9 @@ impl<T> Array for Vec<T> {
         <[T]>::get(self, i)
     }
 
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = &T> + 'i> {
-        Box::new(<[T]>::iter(self))
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self)
     }
 
     #[inline]
@@ -70,6 +149,10 @@ impl<T> Array for Vec<T> {
 
 impl<T> ArrayMut for Vec<T> {
     type Element = T;
+    type IterMut<'i>
+        = std::slice::IterMut<'i, T>
+    where
+        Self: 'i;
     #[inline]
     fn get_mut(&mut self, i: usize) -> Option<&mut T> {
         <[T]>::get_mut(self, i)
@@ -84,11 +167,77 @@ impl<T> ArrayMut for Vec<T> {
     fn push(&mut self, e: T) {
         Vec::push(self, e);
     }
+
+    #[inline]
+    fn insert(&mut self, index: usize, e: T) {
+        Vec::insert(self, index, e);
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(Vec::remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(Vec::swap_remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Extend::extend(self, iter);
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        Vec::retain(self, f);
+    }
+
+    #[inline]
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        <[T]>::sort_by(self, compare);
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        <[T]>::iter_mut(self)
+    }
 }
 
 #[cfg(feature = "c-abi")]
 impl<T> Array for abi_stable::std_types::RVec<T> {
     type Element = T;
+    type Iter<'i>
+        = std::slice::Iter<'i, T>
+    where
+        Self: 'i;
 
     #[inline]
     fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
@@ -98,8 +247,9 @@ impl<T> Array for abi_stable::std_types::RVec<T> {
         <[T]>::get(self, i)
     }
 
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = &T> + 'i> {
-        Box::new(<[T]>::iter(self))
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self)
     }
 
     #[inline]
@@ -110,6 +260,10 @@ impl<T> Array for abi_stable::std_types::RVec<T> {
 #[cfg(feature = "c-abi")]
 impl<T> ArrayMut for abi_stable::std_types::RVec<T> {
     type Element = T;
+    type IterMut<'i>
+        = std::slice::IterMut<'i, T>
+    where
+        Self: 'i;
 
     #[inline]
     fn get_mut(&mut self, i: usize) -> Option<&mut T> {
@@ -125,4 +279,726 @@ impl<T> ArrayMut for abi_stable::std_types::RVec<T> {
     fn push(&mut self, e: T) {
         abi_stable::std_types::RVec::push(self, e);
     }
+
+    #[inline]
+    fn insert(&mut self, index: usize, e: T) {
+        abi_stable::std_types::RVec::insert(self, index, e);
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(abi_stable::std_types::RVec::remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(abi_stable::std_types::RVec::swap_remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        abi_stable::std_types::RVec::truncate(self, len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        abi_stable::std_types::RVec::clear(self);
+    }
+
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Extend::extend(self, iter);
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        abi_stable::std_types::RVec::retain(self, f);
+    }
+
+    #[inline]
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        <[T]>::sort_by(self, compare);
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        <[T]>::iter_mut(self)
+    }
+}
+
+#[cfg(feature = "arena")]
+impl<T> Array for bumpalo::collections::Vec<'_, T> {
+    type Element = T;
+    type Iter<'i>
+        = std::slice::Iter<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
+    where
+        I: SliceIndex<[T]>,
+    {
+        <[T]>::get(self, i)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+#[cfg(feature = "arena")]
+impl<T> ArrayMut for bumpalo::collections::Vec<'_, T> {
+    type Element = T;
+    type IterMut<'i>
+        = std::slice::IterMut<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        <[T]>::get_mut(self, i)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        bumpalo::collections::Vec::pop(self)
+    }
+
+    #[inline]
+    fn push(&mut self, e: T) {
+        bumpalo::collections::Vec::push(self, e);
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize, e: T) {
+        bumpalo::collections::Vec::insert(self, index, e);
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(bumpalo::collections::Vec::remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(bumpalo::collections::Vec::swap_remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        bumpalo::collections::Vec::truncate(self, len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        bumpalo::collections::Vec::clear(self);
+    }
+
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Extend::extend(self, iter);
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        bumpalo::collections::Vec::retain(self, f);
+    }
+
+    #[inline]
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        <[T]>::sort_by(self, compare);
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        <[T]>::iter_mut(self)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Array for smallvec::SmallVec<A> {
+    type Element = A::Item;
+    type Iter<'i>
+        = std::slice::Iter<'i, A::Item>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[A::Item]>>::Output>
+    where
+        I: SliceIndex<[A::Item]>,
+    {
+        <[A::Item]>::get(self, i)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[A::Item]>::iter(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        smallvec::SmallVec::len(self)
+    }
+}
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> ArrayMut for smallvec::SmallVec<A> {
+    type Element = A::Item;
+    type IterMut<'i>
+        = std::slice::IterMut<'i, A::Item>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get_mut(&mut self, i: usize) -> Option<&mut A::Item> {
+        <[A::Item]>::get_mut(self, i)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<A::Item> {
+        smallvec::SmallVec::pop(self)
+    }
+
+    #[inline]
+    fn push(&mut self, e: A::Item) {
+        smallvec::SmallVec::push(self, e);
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize, e: A::Item) {
+        smallvec::SmallVec::insert(self, index, e);
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<A::Item> {
+        if index < self.len() {
+            Some(smallvec::SmallVec::remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> Option<A::Item> {
+        if index < self.len() {
+            Some(smallvec::SmallVec::swap_remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        smallvec::SmallVec::truncate(self, len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        smallvec::SmallVec::clear(self);
+    }
+
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A::Item>,
+    {
+        Extend::extend(self, iter);
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&A::Item) -> bool,
+    {
+        smallvec::SmallVec::retain(self, |e| f(e));
+    }
+
+    #[inline]
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&A::Item, &A::Item) -> Ordering,
+    {
+        <[A::Item]>::sort_by(self, compare);
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        <[A::Item]>::iter_mut(self)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const CAP: usize> Array for arrayvec::ArrayVec<T, CAP> {
+    type Element = T;
+    type Iter<'i>
+        = std::slice::Iter<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
+    where
+        I: SliceIndex<[T]>,
+    {
+        <[T]>::get(self, i)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        arrayvec::ArrayVec::len(self)
+    }
+}
+#[cfg(feature = "arrayvec")]
+impl<T, const CAP: usize> ArrayMut for arrayvec::ArrayVec<T, CAP> {
+    type Element = T;
+    type IterMut<'i>
+        = std::slice::IterMut<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        <[T]>::get_mut(self, i)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        arrayvec::ArrayVec::pop(self)
+    }
+
+    /// Appends e to the end of the `Array`
+    ///
+    /// # Panics
+    ///
+    /// if the array is full, use `try_push` to handle this
+    #[inline]
+    fn push(&mut self, e: T) {
+        arrayvec::ArrayVec::push(self, e);
+    }
+
+    #[inline]
+    fn try_push(&mut self, e: T) -> Result<(), T> {
+        arrayvec::ArrayVec::try_push(self, e).map_err(arrayvec::CapacityError::element)
+    }
+
+    /// Inserts e at position `index`, shifting all elements after it to
+    /// the right
+    ///
+    /// # Panics
+    ///
+    /// if the array is full or `index > len`
+    #[inline]
+    fn insert(&mut self, index: usize, e: T) {
+        arrayvec::ArrayVec::insert(self, index, e);
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(arrayvec::ArrayVec::remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len() {
+            Some(arrayvec::ArrayVec::swap_remove(self, index))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        arrayvec::ArrayVec::truncate(self, len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        arrayvec::ArrayVec::clear(self);
+    }
+
+    /// Appends all elements of `iter` to the end of the `Array`
+    ///
+    /// # Panics
+    ///
+    /// if the array runs out of capacity
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Extend::extend(self, iter);
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        arrayvec::ArrayVec::retain(self, |e| f(e));
+    }
+
+    #[inline]
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        <[T]>::sort_by(self, compare);
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        <[T]>::iter_mut(self)
+    }
+}
+
+/// `get` borrows the deque as one slice, so it only finds elements while the
+/// deque is contiguous and returns `None` otherwise. The `ArrayMut`
+/// operations that can wrap the deque around call `make_contiguous`
+/// afterwards, after `push_front` or other `VecDeque` methods call it
+/// before using `get`.
+impl<T> Array for VecDeque<T> {
+    type Element = T;
+    type Iter<'i>
+        = std::collections::vec_deque::Iter<'i, T>
+    where
+        Self: 'i;
+
+    /// Gets a ref to a value based on n index, returns `None` if the deque
+    /// isn't contiguous
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
+    where
+        I: SliceIndex<[T]>,
+    {
+        match self.as_slices() {
+            (front, []) => front.get(i),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        VecDeque::iter(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+}
+
+/// `push`, `insert` and `extend` keep the deque contiguous, this only moves
+/// elements when they wrapped around the end of the buffer
+impl<T> ArrayMut for VecDeque<T> {
+    type Element = T;
+    type IterMut<'i>
+        = std::collections::vec_deque::IterMut<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        VecDeque::get_mut(self, i)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        VecDeque::pop_back(self)
+    }
+
+    #[inline]
+    fn push(&mut self, e: T) {
+        VecDeque::push_back(self, e);
+        self.make_contiguous();
+    }
+
+    #[inline]
+    fn insert(&mut self, index: usize, e: T) {
+        VecDeque::insert(self, index, e);
+        self.make_contiguous();
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> Option<T> {
+        VecDeque::remove(self, index)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> Option<T> {
+        VecDeque::swap_remove_back(self, index)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        VecDeque::truncate(self, len);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        VecDeque::clear(self);
+    }
+
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Extend::extend(self, iter);
+        self.make_contiguous();
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        VecDeque::retain(self, f);
+    }
+
+    #[inline]
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.make_contiguous().sort_by(compare);
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        VecDeque::iter_mut(self)
+    }
+}
+
+impl<T> Array for Box<[T]> {
+    type Element = T;
+    type Iter<'i>
+        = std::slice::Iter<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
+    where
+        I: SliceIndex<[T]>,
+    {
+        <[T]>::get(self, i)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+}
+
+impl<T> Array for &[T] {
+    type Element = T;
+    type Iter<'i>
+        = std::slice::Iter<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
+    where
+        I: SliceIndex<[T]>,
+    {
+        <[T]>::get(self, i)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+}
+
+impl<T> Array for Arc<[T]> {
+    type Element = T;
+    type Iter<'i>
+        = std::slice::Iter<'i, T>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as SliceIndex<[T]>>::Output>
+    where
+        I: SliceIndex<[T]>,
+    {
+        <[T]>::get(self, i)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        <[T]>::iter(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Array, ArrayMut};
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    fn fill<A>(a: &mut A)
+    where
+        A: ArrayMut<Element = u8>,
+    {
+        a.extend([3, 1, 2]);
+        a.insert(0, 4);
+        a.sort_by(Ord::cmp);
+        a.retain(|e| *e != 2);
+        for e in a.iter_mut() {
+            *e *= 10;
+        }
+    }
+
+    fn elements<A>(a: &A) -> Vec<u8>
+    where
+        A: Array<Element = u8>,
+    {
+        a.iter().copied().collect()
+    }
+
+    #[test]
+    fn vec() {
+        let mut v = Vec::new();
+        fill(&mut v);
+        assert_eq!(elements(&v), [10, 30, 40]);
+        assert_eq!(ArrayMut::remove(&mut v, 7), None);
+        assert_eq!(ArrayMut::swap_remove(&mut v, 0), Some(10));
+        assert_eq!(Array::get(&v, 0..1), Some(&[40][..]));
+    }
+
+    #[test]
+    fn vec_deque() {
+        let mut v = VecDeque::with_capacity(4);
+        fill(&mut v);
+        assert_eq!(elements(&v), [10, 30, 40]);
+        assert_eq!(Array::get(&v, 0..3), Some(&[10, 30, 40][..]));
+
+        // wrap the deque around the end of its buffer
+        let mut v: VecDeque<u8> = VecDeque::with_capacity(4);
+        Extend::extend(&mut v, [1, 2, 3]);
+        v.pop_front();
+        v.pop_front();
+        v.push_back(4);
+        v.push_back(5);
+        v.push_front(0);
+        assert_eq!(elements(&v), [0, 3, 4, 5]);
+        if v.as_slices().1.is_empty() {
+            assert_eq!(Array::get(&v, 1), Some(&3));
+        } else {
+            assert_eq!(Array::get(&v, 1), None);
+        }
+        ArrayMut::insert(&mut v, 1, 9);
+        ArrayMut::push(&mut v, 6);
+        assert_eq!(Array::get(&v, ..), Some(&[0, 9, 3, 4, 5, 6][..]));
+        assert_eq!(ArrayMut::swap_remove(&mut v, 0), Some(0));
+        assert_eq!(ArrayMut::remove(&mut v, 9), None);
+        ArrayMut::truncate(&mut v, 2);
+        assert_eq!(Array::get(&v, ..), Some(&[6, 9][..]));
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn smallvec() {
+        let mut v = smallvec::SmallVec::<[u8; 2]>::new();
+        fill(&mut v);
+        assert_eq!(elements(&v), [10, 30, 40]);
+        assert_eq!(Array::get(&v, 2), Some(&40));
+    }
+
+    #[cfg(feature = "arrayvec")]
+    #[test]
+    fn arrayvec_try_push() {
+        let mut v = arrayvec::ArrayVec::<u8, 4>::new();
+        fill(&mut v);
+        assert_eq!(ArrayMut::try_push(&mut v, 50), Ok(()));
+        assert_eq!(ArrayMut::try_push(&mut v, 60), Err(60));
+        assert_eq!(elements(&v), [10, 30, 40, 50]);
+        assert_eq!(ArrayMut::remove(&mut v, 4), None);
+    }
+
+    #[test]
+    fn read_only() {
+        let b: Box<[u8]> = vec![1, 2].into_boxed_slice();
+        let a: Arc<[u8]> = Arc::from(&[1, 2][..]);
+        let s: &[u8] = &[1, 2];
+        assert_eq!(elements(&b), elements(&a));
+        assert_eq!(elements(&s), [1, 2]);
+        assert_eq!(Array::get(&a, 1), Some(&2));
+        assert!(!Array::is_empty(&b));
+    }
+
+    #[test]
+    fn concrete_iterators() {
+        let mut v = vec![1_u8, 2];
+        let iter: std::slice::Iter<'_, u8> = Array::iter(&v);
+        assert_eq!(iter.rev().copied().collect::<Vec<_>>(), [2, 1]);
+        let iter_mut: std::slice::IterMut<'_, u8> = ArrayMut::iter_mut(&mut v);
+        assert_eq!(iter_mut.len(), 2);
+    }
 }
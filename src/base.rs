@@ -196,6 +196,28 @@ pub trait ValueIntoContainer {
     /// Tries to turn the value into it's object representation
     #[must_use]
     fn into_object(self) -> Option<Self::Object>;
+
+    /// Tries to turn the value into an iterator over the owned elements
+    /// of it's array representation
+    #[must_use]
+    fn into_array_iter(self) -> Option<<Self::Array as IntoIterator>::IntoIter>
+    where
+        Self: Sized,
+        Self::Array: IntoIterator,
+    {
+        self.into_array().map(IntoIterator::into_iter)
+    }
+
+    /// Tries to turn the value into an iterator over the owned key value
+    /// pairs of it's object representation
+    #[must_use]
+    fn into_object_iter(self) -> Option<<Self::Object as IntoIterator>::IntoIter>
+    where
+        Self: Sized,
+        Self::Object: IntoIterator,
+    {
+        self.into_object().map(IntoIterator::into_iter)
+    }
 }
 
 /// A Value that can be serialized and written
@@ -0,0 +1,422 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use crate::{
+    array,
+    base::{TypedValue, ValueAsContainer, ValueAsScalar},
+    object, ValueType,
+};
+
+/// Object safe counterpart of the `Array` trait, iterators are boxed.
+///
+/// Implemented for every `Array`.
+pub trait Array {
+    /// Elements of the array
+    type Element;
+
+    /// Gets a ref to the value at index i
+    #[must_use]
+    fn get(&self, i: usize) -> Option<&Self::Element>;
+
+    /// Iterates over the values
+    #[must_use]
+    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Element> + 'i>;
+
+    /// Number of elements
+    #[must_use]
+    fn len(&self) -> usize;
+
+    /// Returns if the array is empty
+    #[must_use]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<A> Array for A
+where
+    A: array::Array + ?Sized,
+{
+    type Element = A::Element;
+
+    #[inline]
+    fn get(&self, i: usize) -> Option<&Self::Element> {
+        array::Array::get(self, i)
+    }
+
+    #[inline]
+    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Element> + 'i> {
+        Box::new(array::Array::iter(self))
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        array::Array::len(self)
+    }
+}
+
+/// Object safe counterpart of the `Object` trait, iterators are boxed.
+///
+/// Implemented for every `Object`.
+pub trait Object {
+    /// The key in the objects
+    type Key: ?Sized;
+    /// The values in the object
+    type Element;
+
+    /// Iterates over the key value paris
+    #[must_use]
+    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&'i Self::Key, &'i Self::Element)> + 'i>;
+
+    /// Iterates over the keys
+    #[must_use]
+    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Key> + 'i>;
+
+    /// Iterates over the values
+    #[must_use]
+    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Element> + 'i>;
+
+    /// Number of key/value pairs
+    #[must_use]
+    fn len(&self) -> usize;
+
+    /// Returns if the object is empty
+    #[must_use]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<O> Object for O
+where
+    O: object::Object + ?Sized,
+{
+    type Key = O::Key;
+    type Element = O::Element;
+
+    #[inline]
+    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&'i Self::Key, &'i Self::Element)> + 'i> {
+        Box::new(object::Object::iter(self))
+    }
+
+    #[inline]
+    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Key> + 'i> {
+        Box::new(object::Object::keys(self))
+    }
+
+    #[inline]
+    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Element> + 'i> {
+        Box::new(object::Object::values(self))
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        object::Object::len(self)
+    }
+}
+
+/// Object safe value trait, allows storing different value implementations
+/// as `&dyn DynValue` or `Box<dyn DynValue>`.
+///
+/// Implemented for every value implementing the base traits whose
+/// containers hold the value itself and whose keys borrow as `str`.
+pub trait DynValue {
+    /// Gets the type of the current value
+    #[must_use]
+    fn value_type(&self) -> ValueType;
+
+    /// Tries to represent the value as a null
+    #[must_use]
+    fn as_null(&self) -> Option<()>;
+
+    /// Tries to represent the value as a bool
+    #[must_use]
+    fn as_bool(&self) -> Option<bool>;
+
+    /// Tries to represent the value as an i64
+    #[must_use]
+    fn as_i64(&self) -> Option<i64>;
+
+    /// Tries to represent the value as an i128
+    #[must_use]
+    fn as_i128(&self) -> Option<i128>;
+
+    /// Tries to represent the value as an u64
+    #[must_use]
+    fn as_u64(&self) -> Option<u64>;
+
+    /// Tries to represent the value as an u128
+    #[must_use]
+    fn as_u128(&self) -> Option<u128>;
+
+    /// Tries to represent the value as a f64
+    #[must_use]
+    fn as_f64(&self) -> Option<f64>;
+
+    /// Casts the current value to a f64 if possible, this will turn integer
+    /// values into floats.
+    #[must_use]
+    fn cast_f64(&self) -> Option<f64>;
+
+    /// Tries to represent the value as a &str
+    #[must_use]
+    fn as_str(&self) -> Option<&str>;
+
+    /// Gets a value from an object by key, returns `None` if the value
+    /// isn't an object or doesn't contain the key
+    #[must_use]
+    fn get(&self, k: &str) -> Option<&dyn DynValue>;
+
+    /// Gets a value from an array by index, returns `None` if the value
+    /// isn't an array or doesn't contain the index
+    #[must_use]
+    fn get_idx(&self, i: usize) -> Option<&dyn DynValue>;
+
+    /// Number of elements or key value pairs if the value is an array or
+    /// an object
+    #[must_use]
+    fn len(&self) -> Option<usize>;
+
+    /// Returns if the value is an empty array or object
+    #[must_use]
+    fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Iterates over the elements if the value is an array
+    #[must_use]
+    fn iter_array(&self) -> Option<Box<dyn Iterator<Item = &dyn DynValue> + '_>>;
+
+    /// Iterates over the key value pairs if the value is an object
+    #[must_use]
+    fn iter_object(&self) -> Option<Box<dyn Iterator<Item = (&str, &dyn DynValue)> + '_>>;
+
+    /// Returns the value as a trait object
+    #[must_use]
+    fn as_dyn(&self) -> &dyn DynValue;
+}
+
+impl<V> DynValue for V
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: array::Array<Element = V>,
+    V::Object: object::Object<Element = V>,
+    <V::Object as object::Object>::Key: Borrow<str> + Hash + Eq,
+{
+    #[inline]
+    fn value_type(&self) -> ValueType {
+        TypedValue::value_type(self)
+    }
+
+    #[inline]
+    fn as_null(&self) -> Option<()> {
+        ValueAsScalar::as_null(self)
+    }
+
+    #[inline]
+    fn as_bool(&self) -> Option<bool> {
+        ValueAsScalar::as_bool(self)
+    }
+
+    #[inline]
+    fn as_i64(&self) -> Option<i64> {
+        ValueAsScalar::as_i64(self)
+    }
+
+    #[inline]
+    fn as_i128(&self) -> Option<i128> {
+        ValueAsScalar::as_i128(self)
+    }
+
+    #[inline]
+    fn as_u64(&self) -> Option<u64> {
+        ValueAsScalar::as_u64(self)
+    }
+
+    #[inline]
+    fn as_u128(&self) -> Option<u128> {
+        ValueAsScalar::as_u128(self)
+    }
+
+    #[inline]
+    fn as_f64(&self) -> Option<f64> {
+        ValueAsScalar::as_f64(self)
+    }
+
+    #[inline]
+    fn cast_f64(&self) -> Option<f64> {
+        ValueAsScalar::cast_f64(self)
+    }
+
+    #[inline]
+    fn as_str(&self) -> Option<&str> {
+        ValueAsScalar::as_str(self)
+    }
+
+    #[inline]
+    fn get(&self, k: &str) -> Option<&dyn DynValue> {
+        let v = object::Object::get(self.as_object()?, k)?;
+        Some(v)
+    }
+
+    #[inline]
+    fn get_idx(&self, i: usize) -> Option<&dyn DynValue> {
+        let v = array::Array::get(self.as_array()?, i)?;
+        Some(v)
+    }
+
+    #[inline]
+    fn len(&self) -> Option<usize> {
+        self.as_array()
+            .map(array::Array::len)
+            .or_else(|| self.as_object().map(object::Object::len))
+    }
+
+    fn iter_array(&self) -> Option<Box<dyn Iterator<Item = &dyn DynValue> + '_>> {
+        let a = self.as_array()?;
+        Some(Box::new(
+            array::Array::iter(a).map(|v| -> &dyn DynValue { v }),
+        ))
+    }
+
+    fn iter_object(&self) -> Option<Box<dyn Iterator<Item = (&str, &dyn DynValue)> + '_>> {
+        let o = self.as_object()?;
+        Some(Box::new(
+            object::Object::iter(o).map(|(k, v)| -> (&str, &dyn DynValue) { (k.borrow(), v) }),
+        ))
+    }
+
+    #[inline]
+    fn as_dyn(&self) -> &dyn DynValue {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DynValue;
+    use crate::boxed;
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+
+    fn sum(v: &dyn DynValue) -> f64 {
+        if let Some(a) = v.iter_array() {
+            a.map(sum).sum()
+        } else if let Some(o) = v.iter_object() {
+            o.map(|(_, v)| sum(v)).sum()
+        } else {
+            v.cast_f64().unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn scalars() {
+        let values: Vec<Box<dyn DynValue>> = vec![
+            Box::new(()),
+            Box::new(true),
+            Box::new(-1_i64),
+            Box::new(2_u64),
+            Box::new(0.5_f64),
+            Box::new(NativeValue::from("s")),
+        ];
+        let types: Vec<ValueType> = values.iter().map(|v| v.value_type()).collect();
+        assert_eq!(
+            types,
+            [
+                ValueType::Null,
+                ValueType::Bool,
+                ValueType::I64,
+                ValueType::U64,
+                ValueType::F64,
+                ValueType::String
+            ]
+        );
+        assert_eq!(values[0].as_null(), Some(()));
+        assert_eq!(values[1].as_bool(), Some(true));
+        assert_eq!(values[2].as_i64(), Some(-1));
+        assert_eq!(values[2].as_i128(), Some(-1));
+        assert_eq!(values[2].as_u64(), None);
+        assert_eq!(values[3].as_u64(), Some(2));
+        assert_eq!(values[3].as_u128(), Some(2));
+        assert_eq!(values[3].as_f64(), None);
+        assert_eq!(values[3].cast_f64(), Some(2.0));
+        assert_eq!(values[4].as_f64(), Some(0.5));
+        assert_eq!(values[5].as_str(), Some("s"));
+        for v in &values {
+            assert_eq!(v.len(), None);
+            assert_eq!(v.is_empty(), None);
+            assert!(v.get("a").is_none());
+            assert!(v.get_idx(0).is_none());
+            assert!(v.iter_array().is_none());
+            assert!(v.iter_object().is_none());
+        }
+    }
+
+    #[test]
+    fn containers() {
+        let v = NativeValue::from(HashMap::from([
+            ("a".to_string(), NativeValue::from(vec![1_u8, 2, 3])),
+            (
+                "o".to_string(),
+                NativeValue::from(HashMap::from([("x".to_string(), 0.5)])),
+            ),
+            ("e".to_string(), NativeValue::from(Vec::<u8>::new())),
+        ]));
+        let d: &dyn DynValue = v.as_dyn();
+        assert_eq!(d.value_type(), ValueType::Object);
+        assert_eq!(d.len(), Some(3));
+        assert_eq!(d.is_empty(), Some(false));
+        assert!(d.get("missing").is_none());
+        assert!(d.get_idx(0).is_none());
+
+        let a = d.get("a").expect("a");
+        assert_eq!(a.value_type(), ValueType::Array);
+        assert_eq!(a.len(), Some(3));
+        assert_eq!(a.get_idx(2).and_then(DynValue::as_u64), Some(3));
+        assert!(a.get_idx(3).is_none());
+        assert!(a.get("x").is_none());
+        assert!(a.iter_object().is_none());
+
+        let o = d.get("o").expect("o");
+        let pairs: Vec<(&str, Option<f64>)> = o
+            .iter_object()
+            .expect("object")
+            .map(|(k, v)| (k, v.as_f64()))
+            .collect();
+        assert_eq!(pairs, [("x", Some(0.5))]);
+        assert!(o.iter_array().is_none());
+
+        assert_eq!(d.get("e").and_then(DynValue::is_empty), Some(true));
+        assert!((sum(d) - 6.5).abs() < f64::EPSILON);
+
+        let mixed: Vec<&dyn DynValue> = vec![&v, &7_u64, a];
+        assert!((mixed.into_iter().map(sum).sum::<f64>() - 19.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn boxed_containers() {
+        let v = vec![1_u8, 2, 3];
+        let a: &dyn boxed::Array<Element = u8> = &v;
+        assert_eq!(a.len(), 3);
+        assert!(!a.is_empty());
+        assert_eq!(a.get(1), Some(&2));
+        assert_eq!(a.get(3), None);
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), v);
+
+        let m = HashMap::from([("a".to_string(), 1_u8), ("b".to_string(), 2)]);
+        let o: &dyn boxed::Object<Key = String, Element = u8> = &m;
+        assert_eq!(o.len(), 2);
+        assert!(!o.is_empty());
+        let mut keys: Vec<&String> = o.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(o.values().map(|v| u32::from(*v)).sum::<u32>(), 3);
+        let mut pairs: Vec<(&String, &u8)> = o.iter().collect();
+        pairs.sort();
+        assert_eq!(pairs, [(&"a".to_string(), &1), (&"b".to_string(), &2)]);
+
+        let empty: &dyn boxed::Object<Key = String, Element = u8> = &HashMap::new();
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,672 @@
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::hash::Hash;
+use std::io::{self, Write};
+
+use crate::{
+    array::{Array, ArrayMut},
+    base::{TypedValue, ValueAsContainer, ValueAsMutContainer, ValueAsScalar},
+    generator::Node,
+    object::{Object, ObjectMut},
+    ValueBuilder,
+};
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const TAG_POSITIVE_BIGNUM: u64 = 2;
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+
+const FALSE: u8 = 0xf4;
+const TRUE: u8 = 0xf5;
+const NULL: u8 = 0xf6;
+const FLOAT32: u8 = 0xfa;
+const FLOAT64: u8 = 0xfb;
+const BREAK: u8 = 0xff;
+
+/// Encodes a value as CBOR into a Writer, integers use their smallest
+/// encoding and 128 bit integers that do not fit into 64 bit are written
+/// as bignums.
+///
+/// # Errors
+///
+/// Will return `Err` if an IO error is encountered
+pub fn encode<V, W>(value: &V, writer: &mut W) -> io::Result<()>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+    W: Write,
+{
+    match Node::of(value) {
+        Node::Null => writer.write_all(&[NULL]),
+        Node::Bool(b) => writer.write_all(&[if b { TRUE } else { FALSE }]),
+        #[allow(clippy::cast_sign_loss)] // -1 - n is !n for negative numbers
+        Node::I64(n) if n < 0 => write_head(writer, MAJOR_NEGATIVE, !n as u64),
+        #[allow(clippy::cast_sign_loss)] // we checked the sign above
+        Node::I64(n) => write_head(writer, MAJOR_UNSIGNED, n as u64),
+        Node::U64(n) => write_head(writer, MAJOR_UNSIGNED, n),
+        #[allow(clippy::cast_sign_loss)] // -1 - n is !n for negative numbers
+        Node::I128(n) if n < 0 => write_integer(writer, true, !n as u128),
+        #[allow(clippy::cast_sign_loss)] // we checked the sign above
+        Node::I128(n) => write_integer(writer, false, n as u128),
+        Node::U128(n) => write_integer(writer, false, n),
+        Node::F64(n) => {
+            // use single precision if it is lossless
+            #[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+            let single = n as f32;
+            #[allow(clippy::float_cmp)]
+            if f64::from(single) == n || n.is_nan() {
+                writer.write_all(&[FLOAT32])?;
+                writer.write_all(&single.to_be_bytes())
+            } else {
+                writer.write_all(&[FLOAT64])?;
+                writer.write_all(&n.to_be_bytes())
+            }
+        }
+        Node::String(s) => write_text(writer, s),
+        Node::Array(array) => {
+            write_head(writer, MAJOR_ARRAY, array.len() as u64)?;
+            for item in array.iter() {
+                encode(item, writer)?;
+            }
+            Ok(())
+        }
+        Node::Object(object) => {
+            write_head(writer, MAJOR_MAP, object.len() as u64)?;
+            for (k, v) in object.iter() {
+                write_text(writer, k.borrow())?;
+                encode(v, writer)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Encodes a value as CBOR
+#[must_use]
+pub fn to_vec<V>(value: &V) -> Vec<u8>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    let mut out = Vec::new();
+    // writing to a Vec can not fail
+    let _: io::Result<()> = encode(value, &mut out);
+    out
+}
+
+/// Writes the initial byte(s) of an item using the smallest argument encoding
+#[allow(clippy::cast_possible_truncation)] // n < 24
+fn write_head<W: Write>(writer: &mut W, major: u8, n: u64) -> io::Result<()> {
+    let major = major << 5;
+    if n < 24 {
+        writer.write_all(&[major | n as u8])
+    } else if let Ok(n) = u8::try_from(n) {
+        writer.write_all(&[major | 0x18, n])
+    } else if let Ok(n) = u16::try_from(n) {
+        writer.write_all(&[major | 0x19])?;
+        writer.write_all(&n.to_be_bytes())
+    } else if let Ok(n) = u32::try_from(n) {
+        writer.write_all(&[major | 0x1a])?;
+        writer.write_all(&n.to_be_bytes())
+    } else {
+        writer.write_all(&[major | 0x1b])?;
+        writer.write_all(&n.to_be_bytes())
+    }
+}
+
+/// Writes a (possibly) 128 bit integer, `n` is the CBOR argument so for
+/// negative numbers it is `-1 - value`
+fn write_integer<W: Write>(writer: &mut W, negative: bool, n: u128) -> io::Result<()> {
+    let (major, tag) = if negative {
+        (MAJOR_NEGATIVE, TAG_NEGATIVE_BIGNUM)
+    } else {
+        (MAJOR_UNSIGNED, TAG_POSITIVE_BIGNUM)
+    };
+    if let Ok(n) = u64::try_from(n) {
+        return write_head(writer, major, n);
+    }
+    let bytes = n.to_be_bytes();
+    let bytes = &bytes[(n.leading_zeros() / 8) as usize..];
+    write_head(writer, MAJOR_TAG, tag)?;
+    write_head(writer, MAJOR_BYTES, bytes.len() as u64)?;
+    writer.write_all(bytes)
+}
+
+fn write_text<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    write_head(writer, MAJOR_TEXT, s.len() as u64)?;
+    writer.write_all(s.as_bytes())
+}
+
+/// Decoder settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderConfig {
+    /// Maximum nesting of arrays, maps and tags
+    pub max_depth: usize,
+    /// Reject indefinite length items
+    pub strict: bool,
+}
+
+impl Default for DecoderConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 128,
+            strict: false,
+        }
+    }
+}
+
+/// The kinds of errors the CBOR decoder can encounter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CborErrorKind {
+    /// The input ended in the middle of an item
+    Eof,
+    /// There is data left after the first item
+    TrailingData,
+    /// Arrays, maps and tags are nested deeper than the configured limit
+    DepthLimitExceeded,
+    /// An indefinite length item was found in strict mode
+    IndefiniteLength,
+    /// A text string is not valid UTF-8
+    InvalidUtf8,
+    /// A map key is not a text string
+    InvalidKey,
+    /// The initial byte is malformed
+    InvalidItem,
+    /// An integer does not fit into the supported integer types
+    OutOfRange,
+    /// The item (like a byte string) has no representation as a value
+    Unsupported,
+}
+
+impl fmt::Display for CborErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eof => write!(f, "Unexpected end of input"),
+            Self::TrailingData => write!(f, "Trailing data after the item"),
+            Self::DepthLimitExceeded => write!(f, "Nesting depth limit exceeded"),
+            Self::IndefiniteLength => write!(f, "Indefinite length items are not allowed"),
+            Self::InvalidUtf8 => write!(f, "Text string is not valid UTF-8"),
+            Self::InvalidKey => write!(f, "Map keys have to be text strings"),
+            Self::InvalidItem => write!(f, "Malformed item"),
+            Self::OutOfRange => write!(f, "Integer out of range"),
+            Self::Unsupported => write!(f, "Item can not be represented as a value"),
+        }
+    }
+}
+
+/// An error encountered while decoding CBOR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CborError {
+    /// Byte offset of the item that caused the error
+    pub offset: usize,
+    /// What went wrong
+    pub kind: CborErrorKind,
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl std::error::Error for CborError {}
+
+/// Decodes a CBOR item into a value using the default settings
+///
+/// # Errors
+///
+/// if the input is not a single valid CBOR item that can be represented
+/// as a value
+pub fn decode<'input, V>(data: &'input [u8]) -> Result<V, CborError>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<Cow<'input, str>> + Hash + Eq,
+{
+    decode_with(data, DecoderConfig::default())
+}
+
+/// Decodes a CBOR item into a value
+///
+/// # Errors
+///
+/// if the input is not a single valid CBOR item that can be represented
+/// as a value
+pub fn decode_with<'input, V>(data: &'input [u8], config: DecoderConfig) -> Result<V, CborError>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<Cow<'input, str>> + Hash + Eq,
+{
+    let mut decoder = Decoder {
+        data,
+        idx: 0,
+        config,
+    };
+    let value = decoder.value(0)?;
+    if decoder.idx == data.len() {
+        Ok(value)
+    } else {
+        Err(decoder.error(CborErrorKind::TrailingData))
+    }
+}
+
+struct Decoder<'input> {
+    data: &'input [u8],
+    idx: usize,
+    config: DecoderConfig,
+}
+
+impl<'input> Decoder<'input> {
+    fn error(&self, kind: CborErrorKind) -> CborError {
+        CborError {
+            offset: self.idx,
+            kind,
+        }
+    }
+
+    fn read(&mut self, n: usize) -> Result<&'input [u8], CborError> {
+        let data = self.data;
+        // the length comes from the input, compare it against what is left
+        // instead of adding it to the index
+        if n > data.len() - self.idx {
+            return Err(self.error(CborErrorKind::Eof));
+        }
+        let bytes = &data[self.idx..self.idx + n];
+        self.idx += n;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], CborError> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.read(N)?);
+        Ok(bytes)
+    }
+
+    fn peek(&self) -> Result<u8, CborError> {
+        self.data
+            .get(self.idx)
+            .copied()
+            .ok_or_else(|| self.error(CborErrorKind::Eof))
+    }
+
+    /// Reads the argument of an item right after its initial byte, `None`
+    /// means indefinite length
+    fn read_argument(&mut self, info: u8) -> Result<Option<u64>, CborError> {
+        let initial = CborError {
+            offset: self.idx - 1,
+            kind: CborErrorKind::InvalidItem,
+        };
+        Ok(Some(match info {
+            0..=23 => u64::from(info),
+            24 => u64::from(self.read(1)?[0]),
+            25 => u64::from(u16::from_be_bytes(self.read_array()?)),
+            26 => u64::from(u32::from_be_bytes(self.read_array()?)),
+            27 => u64::from_be_bytes(self.read_array()?),
+            31 if self.config.strict => {
+                return Err(CborError {
+                    kind: CborErrorKind::IndefiniteLength,
+                    ..initial
+                })
+            }
+            31 => return Ok(None),
+            _ => return Err(initial),
+        }))
+    }
+
+    /// Consumes a break byte if it is next
+    fn at_break(&mut self) -> Result<bool, CborError> {
+        if self.peek()? == BREAK {
+            self.idx += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Checks if there is another item in a container with `remaining`
+    /// items left, `None` means the container ends with a break byte
+    fn has_next(&mut self, remaining: Option<u64>) -> Result<bool, CborError> {
+        match remaining {
+            Some(n) => Ok(n > 0),
+            None => Ok(!self.at_break()?),
+        }
+    }
+
+    fn read_len(&mut self, n: u64) -> Result<usize, CborError> {
+        usize::try_from(n).map_err(|_| self.error(CborErrorKind::Eof))
+    }
+
+    /// Reads a text string, definite length strings are borrowed from the
+    /// input while indefinite ones have to be joined
+    fn text(&mut self, info: u8) -> Result<Cow<'input, str>, CborError> {
+        let start = self.idx;
+        if let Some(len) = self.read_argument(info)? {
+            let len = self.read_len(len)?;
+            let bytes = self.read(len)?;
+            return std::str::from_utf8(bytes)
+                .map(Cow::Borrowed)
+                .map_err(|_| CborError {
+                    offset: start,
+                    kind: CborErrorKind::InvalidUtf8,
+                });
+        }
+        let mut s = String::new();
+        while !self.at_break()? {
+            let initial = self.read(1)?[0];
+            if initial >> 5 != MAJOR_TEXT || initial & 0x1f == 31 {
+                return Err(CborError {
+                    offset: self.idx - 1,
+                    kind: CborErrorKind::InvalidItem,
+                });
+            }
+            s.push_str(&self.text(initial & 0x1f)?);
+        }
+        Ok(Cow::Owned(s))
+    }
+
+    fn value<V>(&mut self, depth: usize) -> Result<V, CborError>
+    where
+        V: ValueBuilder<'input> + ValueAsMutContainer,
+        V::Array: ArrayMut<Element = V>,
+        V::Object: ObjectMut<Element = V>,
+        <V::Object as ObjectMut>::Key: From<Cow<'input, str>> + Hash + Eq,
+    {
+        let start = self.idx;
+        let initial = self.read(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        match major {
+            MAJOR_UNSIGNED => self
+                .read_argument(info)?
+                .map(V::from)
+                .ok_or_else(|| self.error(CborErrorKind::InvalidItem)),
+            MAJOR_NEGATIVE => {
+                let n = self
+                    .read_argument(info)?
+                    .ok_or_else(|| self.error(CborErrorKind::InvalidItem))?;
+                negative(n).ok_or(CborError {
+                    offset: start,
+                    kind: CborErrorKind::OutOfRange,
+                })
+            }
+            MAJOR_TEXT => self.text(info).map(V::from),
+            MAJOR_ARRAY | MAJOR_MAP | MAJOR_TAG if depth >= self.config.max_depth => {
+                Err(CborError {
+                    offset: start,
+                    kind: CborErrorKind::DepthLimitExceeded,
+                })
+            }
+            MAJOR_ARRAY => {
+                let len = self.read_argument(info)?;
+                // never trust the length for pre-allocation more than the input
+                let capacity = len.map_or(0, |len| len.min((self.data.len() - self.idx) as u64));
+                let mut array = V::array_with_capacity(self.read_len(capacity)?);
+                let mut remaining = len;
+                while self.has_next(remaining)? {
+                    let item = self.value(depth + 1)?;
+                    if let Some(array) = array.as_array_mut() {
+                        array.push(item);
+                    }
+                    remaining = remaining.map(|n| n - 1);
+                }
+                Ok(array)
+            }
+            MAJOR_MAP => {
+                let len = self.read_argument(info)?;
+                let capacity = len.map_or(0, |len| len.min((self.data.len() - self.idx) as u64));
+                let mut object = V::object_with_capacity(self.read_len(capacity)?);
+                let mut remaining = len;
+                while self.has_next(remaining)? {
+                    let initial = self.read(1)?[0];
+                    if initial >> 5 != MAJOR_TEXT {
+                        return Err(CborError {
+                            offset: self.idx - 1,
+                            kind: CborErrorKind::InvalidKey,
+                        });
+                    }
+                    let key = self.text(initial & 0x1f)?;
+                    let item: V = self.value(depth + 1)?;
+                    if let Some(object) = object.as_object_mut() {
+                        let _: Option<_> = object.insert(key, item);
+                    }
+                    remaining = remaining.map(|n| n - 1);
+                }
+                Ok(object)
+            }
+            MAJOR_TAG => {
+                let tag = self
+                    .read_argument(info)?
+                    .ok_or_else(|| self.error(CborErrorKind::InvalidItem))?;
+                match tag {
+                    TAG_POSITIVE_BIGNUM | TAG_NEGATIVE_BIGNUM => {
+                        self.bignum(tag == TAG_NEGATIVE_BIGNUM, start)
+                    }
+                    // other tags only add semantics, we keep the content, tags
+                    // count as nesting so chains of them are bounded too
+                    _ => self.value(depth + 1),
+                }
+            }
+            MAJOR_SIMPLE => match info {
+                20 => Ok(V::from(false)),
+                21 => Ok(V::from(true)),
+                22 | 23 => Ok(V::null()),
+                25 => Ok(V::from(f16_to_f64(u16::from_be_bytes(self.read_array()?)))),
+                26 => Ok(V::from(f32::from_be_bytes(self.read_array()?))),
+                27 => Ok(V::from(f64::from_be_bytes(self.read_array()?))),
+                _ => Err(CborError {
+                    offset: start,
+                    kind: CborErrorKind::Unsupported,
+                }),
+            },
+            // byte strings
+            _ => Err(CborError {
+                offset: start,
+                kind: CborErrorKind::Unsupported,
+            }),
+        }
+    }
+
+    #[cfg(feature = "128bit")]
+    fn bignum<V>(&mut self, negative: bool, start: usize) -> Result<V, CborError>
+    where
+        V: ValueBuilder<'input>,
+    {
+        let out_of_range = CborError {
+            offset: start,
+            kind: CborErrorKind::OutOfRange,
+        };
+        let initial = self.read(1)?[0];
+        if initial >> 5 != MAJOR_BYTES || initial & 0x1f == 31 {
+            return Err(self.error(CborErrorKind::InvalidItem));
+        }
+        let len = self
+            .read_argument(initial & 0x1f)?
+            .ok_or_else(|| self.error(CborErrorKind::InvalidItem))?;
+        let len = self.read_len(len)?;
+        let bytes = self.read(len)?;
+        // leading zeros do not change the value
+        let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        let bytes = &bytes[first..];
+        if bytes.len() > 16 {
+            return Err(out_of_range);
+        }
+        let n = bytes.iter().fold(0_u128, |n, b| n << 8 | u128::from(*b));
+        if !negative {
+            return Ok(u64::try_from(n).map_or_else(|_| V::from(n), V::from));
+        }
+        let n = i128::try_from(n).map_err(|_| out_of_range)?;
+        let n = -1 - n;
+        Ok(i64::try_from(n).map_or_else(|_| V::from(n), V::from))
+    }
+
+    #[cfg(not(feature = "128bit"))]
+    #[allow(clippy::unused_self)]
+    fn bignum<V>(&mut self, _negative: bool, start: usize) -> Result<V, CborError>
+    where
+        V: ValueBuilder<'input>,
+    {
+        Err(CborError {
+            offset: start,
+            kind: CborErrorKind::OutOfRange,
+        })
+    }
+}
+
+/// Turns the argument of a negative integer into a value
+#[cfg_attr(feature = "128bit", allow(clippy::unnecessary_wraps))]
+fn negative<'input, V>(n: u64) -> Option<V>
+where
+    V: ValueBuilder<'input>,
+{
+    if let Ok(n) = i64::try_from(n) {
+        return Some(V::from(-1 - n));
+    }
+    #[cfg(feature = "128bit")]
+    {
+        Some(V::from(-1 - i128::from(n)))
+    }
+    #[cfg(not(feature = "128bit"))]
+    {
+        None
+    }
+}
+
+/// Converts an IEEE 754 half precision float
+fn f16_to_f64(half: u16) -> f64 {
+    let exponent = i32::from((half >> 10) & 0x1f);
+    let mantissa = f64::from(half & 0x3ff);
+    let value = match exponent {
+        0 => mantissa * 2_f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2_f64.powi(exponent - 25),
+    };
+    if half & 0x8000 == 0 {
+        value
+    } else {
+        -value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, decode_with, to_vec, CborError, CborErrorKind, DecoderConfig};
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+
+    fn err(data: &[u8]) -> CborErrorKind {
+        decode::<NativeValue>(data).expect_err("invalid").kind
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut object = HashMap::new();
+        object.insert("k".to_string(), NativeValue::from(vec![-1_i64, 1 << 40]));
+        object.insert("ü".to_string(), NativeValue::from(()));
+        let v = NativeValue::from(vec![
+            NativeValue::from(object),
+            NativeValue::from(u64::MAX),
+            NativeValue::from(i64::MIN),
+            NativeValue::from(0.5),
+            NativeValue::from(false),
+            NativeValue::from("text"),
+            NativeValue::from(Vec::<u8>::new()),
+        ]);
+        let bytes = to_vec(&v);
+        assert_eq!(decode::<NativeValue>(&bytes), Ok(v));
+    }
+
+    #[cfg(feature = "128bit")]
+    #[test]
+    fn bignums() {
+        for n in [i128::MIN, i128::from(i64::MIN) - 1] {
+            let v = NativeValue::from(n);
+            assert_eq!(decode::<NativeValue>(&to_vec(&v)), Ok(v));
+        }
+        let v = NativeValue::from(u128::MAX);
+        assert_eq!(decode::<NativeValue>(&to_vec(&v)), Ok(v));
+    }
+
+    #[test]
+    fn indefinite_length() {
+        // [_ "a", {_ "b": 1}] with a chunked string
+        let data = [
+            0x9f, 0x7f, 0x61, b'a', 0xff, 0xbf, 0x61, b'b', 0x01, 0xff, 0xff,
+        ];
+        let v = decode::<NativeValue>(&data).expect("valid");
+        assert_eq!(v.encode(), r#"["a",{"b":1}]"#);
+        let strict = DecoderConfig {
+            strict: true,
+            ..DecoderConfig::default()
+        };
+        assert_eq!(
+            decode_with::<NativeValue>(&data, strict),
+            Err(CborError {
+                offset: 0,
+                kind: CborErrorKind::IndefiniteLength
+            })
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(err(&[]), CborErrorKind::Eof);
+        assert_eq!(err(&[0x82, 0x01]), CborErrorKind::Eof);
+        assert_eq!(err(&[0x01, 0x02]), CborErrorKind::TrailingData);
+        assert_eq!(err(&[0x62, 0xc3, 0x28]), CborErrorKind::InvalidUtf8);
+        assert_eq!(err(&[0xa1, 0x01, 0x01]), CborErrorKind::InvalidKey);
+        assert_eq!(err(&[0x1c]), CborErrorKind::InvalidItem);
+        assert_eq!(err(&[0x41, 0x00]), CborErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn huge_lengths() {
+        // a text string claiming u64::MAX bytes must not overflow the index
+        let mut data = vec![0x7b];
+        data.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(err(&data), CborErrorKind::Eof);
+        // the same inside a container that already advanced the index
+        let mut data = vec![0x82, 0x00, 0x7b];
+        data.extend_from_slice(&(u64::MAX - 1).to_be_bytes());
+        assert_eq!(err(&data), CborErrorKind::Eof);
+        let mut data = vec![0x9b];
+        data.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert_eq!(err(&data), CborErrorKind::Eof);
+    }
+
+    #[test]
+    fn depth_limit() {
+        let config = DecoderConfig {
+            max_depth: 4,
+            ..DecoderConfig::default()
+        };
+        assert!(decode_with::<NativeValue>(&[0x81, 0x81, 0x81, 0x81, 0x00], config).is_ok());
+        assert_eq!(
+            decode_with::<NativeValue>(&[0x81, 0x81, 0x81, 0x81, 0x81, 0x00], config)
+                .map_err(|e| e.kind),
+            Err(CborErrorKind::DepthLimitExceeded)
+        );
+        // long chains of tags used to recurse without a bound
+        let mut data = vec![0xc6; 1_000_000];
+        data.push(0x00);
+        assert_eq!(err(&data), CborErrorKind::DepthLimitExceeded);
+        assert_eq!(
+            decode::<NativeValue>(&[0xc6, 0xc6, 0x01]),
+            Ok(NativeValue::from(1))
+        );
+    }
+}
@@ -0,0 +1,767 @@
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::{
+    array::Array,
+    base::ValueIntoString,
+    derived::{
+        TypedScalarValue, ValueTryAsContainer, ValueTryAsScalar, ValueTryIntoContainer,
+        ValueTryIntoString,
+    },
+    generator::{BaseGenerator, DumpGenerator},
+    object::Object,
+    TryTypeError, Value, ValueType,
+};
+
+/// A segment of the path to the value that failed to convert
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// An index into an array
+    Index(usize),
+    /// A key in an object, displayed as `.key` when it only contains
+    /// alphanumeric characters and `_`, otherwise as a quoted and escaped
+    /// `["key"]`
+    Key(String),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(i) => write!(f, "[{i}]"),
+            Self::Key(k) if !k.is_empty() && k.chars().all(|c| c.is_alphanumeric() || c == '_') => {
+                write!(f, ".{k}")
+            }
+            Self::Key(k) => {
+                let mut g = DumpGenerator::new();
+                g.write_string(k).map_err(|_| fmt::Error)?;
+                write!(f, "[{}]", g.consume())
+            }
+        }
+    }
+}
+
+/// The reason a conversion failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FromValueErrorKind {
+    /// The value had the wrong type or was out of range for the target
+    Type(TryTypeError),
+    /// An array had the wrong number of elements for a tuple or fixed size array
+    Length {
+        /// The expected number of elements
+        expected: usize,
+        /// The actual number of elements
+        got: usize,
+    },
+}
+
+impl fmt::Display for FromValueErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Type(e) => write!(f, "{e}"),
+            Self::Length { expected, got } => {
+                write!(f, "Expected {expected} elements, got {got}")
+            }
+        }
+    }
+}
+
+/// An error thrown by `FromValue`, containing the path to the failing value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FromValueError {
+    /// The path from the value that failed to convert up to the root, the
+    /// innermost segment comes first and it is empty if the root value itself
+    /// failed
+    pub path: Vec<PathSegment>,
+    /// The reason the conversion failed
+    pub kind: FromValueErrorKind,
+}
+
+impl FromValueError {
+    /// Adds the segment of the enclosing container to the path, used when
+    /// the error bubbles up from a nested value
+    #[must_use]
+    pub fn at(mut self, segment: PathSegment) -> Self {
+        self.path.push(segment);
+        self
+    }
+}
+
+impl From<TryTypeError> for FromValueError {
+    fn from(e: TryTypeError) -> Self {
+        Self {
+            path: Vec::new(),
+            kind: FromValueErrorKind::Type(e),
+        }
+    }
+}
+
+impl fmt::Display for FromValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.kind);
+        }
+        write!(f, "At $")?;
+        for s in self.path.iter().rev() {
+            write!(f, "{s}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl std::error::Error for FromValueError {}
+
+/// Conversion of a value into a rust type, the value counterpart of
+/// `TryFrom<&V>` which the orphan rules prevent for foreign types.
+pub trait FromValue: Sized {
+    /// Converts a value into `Self`
+    /// # Errors
+    /// if the value, or any value nested in it, doesn't match the target type
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError>;
+}
+
+/// Consuming conversion of a value into a rust type, strings and nested
+/// values are moved out of the value instead of being copied.
+pub trait FromValueOwned: Sized {
+    /// Converts a value into `Self`
+    /// # Errors
+    /// if the value, or any value nested in it, doesn't match the target type
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError>;
+}
+
+/// A `Value` that can be taken apart by `FromValueOwned`.
+///
+/// It is implemented for every `Value` whose strings and keys convert into
+/// `String` and whose containers can be iterated by value.
+pub trait ValueIntoParts: Value {
+    /// Iterator over the elements of an array
+    type Elements: Iterator<Item = Self>;
+    /// Iterator over the entries of an object
+    type Entries: Iterator<Item = (String, Self)>;
+
+    /// Tries to turn the value into a `String`
+    /// # Errors
+    /// if the value is not a string
+    fn try_into_owned_string(self) -> Result<String, TryTypeError>;
+
+    /// Tries to turn the value into an iterator over its elements
+    /// # Errors
+    /// if the value is not an array
+    fn try_into_elements(self) -> Result<Self::Elements, TryTypeError>;
+
+    /// Tries to turn the value into an iterator over its entries
+    /// # Errors
+    /// if the value is not an object
+    fn try_into_entries(self) -> Result<Self::Entries, TryTypeError>;
+}
+
+/// An entry yielded when consuming an object
+pub trait IntoEntry<V> {
+    /// Turns the entry into an owned key and its value
+    fn into_entry(self) -> (String, V);
+}
+
+impl<K, V> IntoEntry<V> for (K, V)
+where
+    K: Into<String>,
+{
+    #[inline]
+    fn into_entry(self) -> (String, V) {
+        (self.0.into(), self.1)
+    }
+}
+
+type EntryItem<V> = <<V as Value>::ValueObject as IntoIterator>::Item;
+
+impl<V> ValueIntoParts for V
+where
+    V: Value,
+    <V as ValueIntoString>::String: Into<String>,
+    V::ValueArray: IntoIterator<Item = V>,
+    V::ValueObject: IntoIterator,
+    EntryItem<V>: IntoEntry<V>,
+{
+    type Elements = <V::ValueArray as IntoIterator>::IntoIter;
+    type Entries =
+        std::iter::Map<<V::ValueObject as IntoIterator>::IntoIter, fn(EntryItem<V>) -> (String, V)>;
+
+    #[inline]
+    fn try_into_owned_string(self) -> Result<String, TryTypeError> {
+        self.try_into_string().map(Into::into)
+    }
+
+    #[inline]
+    fn try_into_elements(self) -> Result<Self::Elements, TryTypeError> {
+        self.try_into_array().map(IntoIterator::into_iter)
+    }
+
+    #[inline]
+    fn try_into_entries(self) -> Result<Self::Entries, TryTypeError> {
+        self.try_into_object()
+            .map(|o| o.into_iter().map(IntoEntry::into_entry as fn(_) -> _))
+    }
+}
+
+/// Parses any value into a type implementing `FromValue`
+pub trait ValueParse {
+    /// Converts the value into `T`, like `str::parse`
+    /// # Errors
+    /// if the value, or any value nested in it, doesn't match the target type
+    fn parse_as<T: FromValue>(&self) -> Result<T, FromValueError>;
+
+    /// Converts the value into `T` consuming it
+    /// # Errors
+    /// if the value, or any value nested in it, doesn't match the target type
+    fn parse_into<T: FromValueOwned>(self) -> Result<T, FromValueError>
+    where
+        Self: ValueIntoParts;
+}
+
+impl<V> ValueParse for V
+where
+    V: Value,
+{
+    #[inline]
+    fn parse_as<T: FromValue>(&self) -> Result<T, FromValueError> {
+        T::from_value(self)
+    }
+
+    #[inline]
+    fn parse_into<T: FromValueOwned>(self) -> Result<T, FromValueError>
+    where
+        Self: ValueIntoParts,
+    {
+        T::from_value_owned(self)
+    }
+}
+
+macro_rules! scalar {
+    ($($t:ty => $f:ident),*) => {
+        $(
+            impl FromValue for $t {
+                #[inline]
+                fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+                    Ok(value.$f()?)
+                }
+            }
+
+            impl FromValueOwned for $t {
+                #[inline]
+                fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+                    Self::from_value(&value)
+                }
+            }
+        )*
+    };
+}
+
+/// `isize` has no accessor of its own, it is read as an `i64`
+trait TryAsIsize {
+    fn try_as_isize(&self) -> Result<isize, TryTypeError>;
+}
+
+impl<V: Value> TryAsIsize for V {
+    #[inline]
+    fn try_as_isize(&self) -> Result<isize, TryTypeError> {
+        isize::try_from(self.try_as_i64()?).map_err(|_| TryTypeError {
+            expected: ValueType::I64,
+            got: self.value_type(),
+        })
+    }
+}
+
+scalar!(
+    bool => try_as_bool,
+    i8 => try_as_i8,
+    i16 => try_as_i16,
+    i32 => try_as_i32,
+    i64 => try_as_i64,
+    isize => try_as_isize,
+    i128 => try_as_i128,
+    u8 => try_as_u8,
+    u16 => try_as_u16,
+    u32 => try_as_u32,
+    u64 => try_as_u64,
+    u128 => try_as_u128,
+    usize => try_as_usize,
+    f32 => try_as_f32,
+    f64 => try_as_f64,
+    char => try_as_char
+);
+
+impl FromValue for String {
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        Ok(value.try_as_str()?.to_owned())
+    }
+}
+
+impl FromValueOwned for String {
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        Ok(value.try_into_owned_string()?)
+    }
+}
+
+impl FromValue for () {
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        if value.is_null() {
+            Ok(())
+        } else {
+            Err(TryTypeError {
+                expected: ValueType::Null,
+                got: value.value_type(),
+            }
+            .into())
+        }
+    }
+}
+
+impl FromValueOwned for () {
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        Self::from_value(&value)
+    }
+}
+
+/// `null` is converted to `None`, everything else to `Some`
+impl<T> FromValue for Option<T>
+where
+    T: FromValue,
+{
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::from_value(value).map(Some)
+        }
+    }
+}
+
+impl<T> FromValueOwned for Option<T>
+where
+    T: FromValueOwned,
+{
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::from_value_owned(value).map(Some)
+        }
+    }
+}
+
+impl<T> FromValue for Box<T>
+where
+    T: FromValue,
+{
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        T::from_value(value).map(Box::new)
+    }
+}
+
+impl<T> FromValueOwned for Box<T>
+where
+    T: FromValueOwned,
+{
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        T::from_value_owned(value).map(Box::new)
+    }
+}
+
+fn length_error(expected: usize, got: usize) -> FromValueError {
+    FromValueError {
+        path: Vec::new(),
+        kind: FromValueErrorKind::Length { expected, got },
+    }
+}
+
+/// Converts the element at `i`, recording the index on failure
+fn nth<V, T>(a: &V::ValueArray, i: usize) -> Result<T, FromValueError>
+where
+    V: Value,
+    T: FromValue,
+{
+    a.get(i)
+        .map_or_else(|| Err(length_error(i + 1, a.len())), T::from_value)
+        .map_err(|e| e.at(PathSegment::Index(i)))
+}
+
+/// Converts every element of an array, recording the index on failure
+fn elements<V, T, C>(value: &V) -> Result<C, FromValueError>
+where
+    V: Value,
+    T: FromValue,
+    C: FromIterator<T>,
+{
+    value
+        .try_as_array()?
+        .iter()
+        .enumerate()
+        .map(|(i, v)| T::from_value(v).map_err(|e| e.at(PathSegment::Index(i))))
+        .collect()
+}
+
+/// Converts the next element of a consumed array, recording the index on
+/// failure
+fn nth_owned<V, T>(a: &mut std::vec::IntoIter<V>, i: usize, len: usize) -> Result<T, FromValueError>
+where
+    V: ValueIntoParts,
+    T: FromValueOwned,
+{
+    a.next()
+        .map_or_else(|| Err(length_error(i + 1, len)), T::from_value_owned)
+        .map_err(|e| e.at(PathSegment::Index(i)))
+}
+
+/// Converts and consumes every element of an array, recording the index on
+/// failure
+fn elements_owned<V, T, C>(value: V) -> Result<C, FromValueError>
+where
+    V: ValueIntoParts,
+    T: FromValueOwned,
+    C: FromIterator<T>,
+{
+    value
+        .try_into_elements()?
+        .enumerate()
+        .map(|(i, v)| T::from_value_owned(v).map_err(|e| e.at(PathSegment::Index(i))))
+        .collect()
+}
+
+/// Converts and consumes every value of an object, recording the key on
+/// failure
+fn entries_owned<V, T, C>(value: V) -> Result<C, FromValueError>
+where
+    V: ValueIntoParts,
+    T: FromValueOwned,
+    C: FromIterator<(String, T)>,
+{
+    value
+        .try_into_entries()?
+        .map(|(k, v)| match T::from_value_owned(v) {
+            Ok(v) => Ok((k, v)),
+            Err(e) => Err(e.at(PathSegment::Key(k))),
+        })
+        .collect()
+}
+
+/// Converts every value of an object, recording the key on failure
+fn entries<V, T, C>(value: &V) -> Result<C, FromValueError>
+where
+    V: Value,
+    T: FromValue,
+    C: FromIterator<(String, T)>,
+{
+    value
+        .try_as_object()?
+        .iter()
+        .map(|(k, v)| {
+            let k: &str = k.borrow();
+            T::from_value(v)
+                .map(|v| (k.to_owned(), v))
+                .map_err(|e| e.at(PathSegment::Key(k.to_owned())))
+        })
+        .collect()
+}
+
+impl<T> FromValue for Vec<T>
+where
+    T: FromValue,
+{
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        elements(value)
+    }
+}
+
+impl<T> FromValueOwned for Vec<T>
+where
+    T: FromValueOwned,
+{
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        elements_owned(value)
+    }
+}
+
+impl<T> FromValue for VecDeque<T>
+where
+    T: FromValue,
+{
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        elements(value)
+    }
+}
+
+impl<T> FromValueOwned for VecDeque<T>
+where
+    T: FromValueOwned,
+{
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        elements_owned(value)
+    }
+}
+
+impl<T, const N: usize> FromValue for [T; N]
+where
+    T: FromValue,
+{
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        let v: Vec<T> = elements(value)?;
+        v.try_into().map_err(|v: Vec<T>| length_error(N, v.len()))
+    }
+}
+
+impl<T, const N: usize> FromValueOwned for [T; N]
+where
+    T: FromValueOwned,
+{
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        let v: Vec<T> = elements_owned(value)?;
+        v.try_into().map_err(|v: Vec<T>| length_error(N, v.len()))
+    }
+}
+
+impl<T, S> FromValue for HashMap<String, T, S>
+where
+    T: FromValue,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        entries(value)
+    }
+}
+
+impl<T, S> FromValueOwned for HashMap<String, T, S>
+where
+    T: FromValueOwned,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        entries_owned(value)
+    }
+}
+
+impl<T> FromValue for BTreeMap<String, T>
+where
+    T: FromValue,
+{
+    #[inline]
+    fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+        entries(value)
+    }
+}
+
+impl<T> FromValueOwned for BTreeMap<String, T>
+where
+    T: FromValueOwned,
+{
+    #[inline]
+    fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+        entries_owned(value)
+    }
+}
+
+/// Tuples are read from arrays of exactly the same length
+macro_rules! tuple {
+    ($len:expr => $($t:ident $i:tt),+) => {
+        impl<$($t),+> FromValue for ($($t,)+)
+        where
+            $($t: FromValue,)+
+        {
+            fn from_value<V: Value>(value: &V) -> Result<Self, FromValueError> {
+                let a = value.try_as_array()?;
+                if a.len() != $len {
+                    return Err(length_error($len, a.len()));
+                }
+                Ok(($(nth::<V, $t>(a, $i)?,)+))
+            }
+        }
+
+        impl<$($t),+> FromValueOwned for ($($t,)+)
+        where
+            $($t: FromValueOwned,)+
+        {
+            fn from_value_owned<V: ValueIntoParts>(value: V) -> Result<Self, FromValueError> {
+                let a: Vec<V> = value.try_into_elements()?.collect();
+                if a.len() != $len {
+                    return Err(length_error($len, a.len()));
+                }
+                let mut a = a.into_iter();
+                Ok(($(nth_owned::<V, $t>(&mut a, $i, $len)?,)+))
+            }
+        }
+    };
+}
+
+tuple!(1 => A 0);
+tuple!(2 => A 0, B 1);
+tuple!(3 => A 0, B 1, C 2);
+tuple!(4 => A 0, B 1, C 2, D 3);
+tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+tuple!(7 => A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple!(8 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+#[cfg(test)]
+mod test {
+    use super::{FromValueError, FromValueErrorKind, PathSegment};
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn object(entries: Vec<(&str, NativeValue)>) -> NativeValue {
+        let map: HashMap<String, NativeValue> = entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        NativeValue::from(map)
+    }
+
+    fn array(items: Vec<NativeValue>) -> NativeValue {
+        NativeValue::from(items)
+    }
+
+    /// Checks the borrowing and the consuming conversion agree
+    fn both<T>(value: NativeValue) -> Result<T, FromValueError>
+    where
+        T: FromValue + FromValueOwned + PartialEq + std::fmt::Debug,
+    {
+        let borrowed = value.parse_as::<T>();
+        let owned = value.parse_into::<T>();
+        assert_eq!(borrowed, owned);
+        owned
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(both::<u64>(NativeValue::from(7_u64)), Ok(7));
+        assert_eq!(both::<i8>(NativeValue::from(-7_i64)), Ok(-7));
+        assert!(both::<u8>(NativeValue::from(300_u64)).is_err());
+        assert_eq!(both::<isize>(NativeValue::from(-7_i64)), Ok(-7));
+        assert_eq!(
+            both::<isize>(NativeValue::from("x")).map_err(|e| e.kind),
+            Err(FromValueErrorKind::Type(TryTypeError {
+                expected: ValueType::I64,
+                got: ValueType::String,
+            }))
+        );
+        assert_eq!(both::<bool>(NativeValue::from(true)), Ok(true));
+        assert_eq!(both::<String>(NativeValue::from("snow")), Ok("snow".into()));
+        assert_eq!(both::<()>(NativeValue::null()), Ok(()));
+        assert_eq!(both::<Option<u64>>(NativeValue::null()), Ok(None));
+        assert_eq!(both::<Option<u64>>(NativeValue::from(1_u64)), Ok(Some(1)));
+        assert_eq!(both::<Box<f64>>(NativeValue::from(1.5)), Ok(Box::new(1.5)));
+    }
+
+    #[test]
+    fn nested() {
+        let value = object(vec![
+            (
+                "a",
+                array(vec![NativeValue::from("x"), NativeValue::null()]),
+            ),
+            (
+                "b",
+                array(vec![
+                    NativeValue::from("y"),
+                    array(vec![NativeValue::from(true)]),
+                ]),
+            ),
+        ]);
+        let parsed = both::<BTreeMap<String, (String, Option<Vec<bool>>)>>(value);
+        let mut expected = BTreeMap::new();
+        expected.insert("a".to_string(), ("x".to_string(), None));
+        expected.insert("b".to_string(), ("y".to_string(), Some(vec![true])));
+        assert_eq!(parsed, Ok(expected));
+    }
+
+    #[test]
+    fn path_display() {
+        let value = object(vec![(
+            "a",
+            array(vec![NativeValue::from(1_u64), NativeValue::from("x")]),
+        )]);
+        let e = both::<HashMap<String, Vec<u64>>>(value).expect_err("string in array");
+        assert_eq!(
+            e.path,
+            vec![PathSegment::Index(1), PathSegment::Key("a".into())]
+        );
+        assert_eq!(e.to_string(), "At $.a[1]: Expected type u64, got string");
+
+        let e = both::<String>(NativeValue::null()).expect_err("null");
+        assert!(e.path.is_empty());
+        assert_eq!(e.to_string(), "Expected type string, got null");
+
+        let e = FromValueError::from(TryTypeError {
+            expected: ValueType::U64,
+            got: ValueType::String,
+        })
+        .at(PathSegment::Key("b\"]".into()))
+        .at(PathSegment::Key("a.b[0]".into()))
+        .at(PathSegment::Key(String::new()))
+        .at(PathSegment::Key("é_1".into()));
+        assert_eq!(
+            e.to_string(),
+            r#"At $.é_1[""]["a.b[0]"]["b\"]"]: Expected type u64, got string"#
+        );
+    }
+
+    #[test]
+    fn tuple_length() {
+        let value = array(vec![
+            NativeValue::from(1_u64),
+            NativeValue::from(2_u64),
+            NativeValue::from(3_u64),
+        ]);
+        let e = both::<(u64, u64)>(value).expect_err("too long");
+        assert_eq!(
+            e.kind,
+            FromValueErrorKind::Length {
+                expected: 2,
+                got: 3
+            }
+        );
+        assert_eq!(e.to_string(), "Expected 2 elements, got 3");
+
+        let value = array(vec![
+            array(vec![NativeValue::from(1_u64), NativeValue::from("x")]),
+            array(vec![NativeValue::from(1_u64)]),
+        ]);
+        let e = both::<Vec<(u64, String)>>(value).expect_err("too short");
+        assert_eq!(e.path, vec![PathSegment::Index(1)]);
+        assert_eq!(e.to_string(), "At $[1]: Expected 2 elements, got 1");
+    }
+
+    #[test]
+    fn array_length() {
+        let value = array(vec![NativeValue::from(1_u64), NativeValue::from(2_u64)]);
+        assert_eq!(both::<[u64; 2]>(value.clone()), Ok([1, 2]));
+        let e = both::<[u64; 3]>(value).expect_err("too short");
+        assert_eq!(
+            e.kind,
+            FromValueErrorKind::Length {
+                expected: 3,
+                got: 2
+            }
+        );
+
+        let value = object(vec![(
+            "k",
+            array(vec![NativeValue::from(1_u64), NativeValue::from(2_u64)]),
+        )]);
+        let e = both::<HashMap<String, [u64; 1]>>(value).expect_err("too long");
+        assert_eq!(e.to_string(), "At $.k: Expected 1 elements, got 2");
+    }
+}
@@ -1,6 +1,11 @@
-use std::{borrow::Borrow, hash::Hash};
+use std::{borrow::Borrow, cmp::Ordering, hash::Hash};
 
-use crate::{array::Array, object::Object, AccessError, TryTypeError};
+use crate::{
+    array::{Array, ArrayMut},
+    base::ValueAsMutContainer,
+    object::{Object, ObjectEntry, ObjectMut},
+    AccessError, TryTypeError,
+};
 
 /// `try_as_*` access to scalar value types
 pub trait ValueTryAsScalar {
@@ -592,6 +597,140 @@ pub trait MutableObject {
     where
         Self::Key: Borrow<Q> + Hash + Eq,
         Q: ?Sized + Hash + Eq + Ord;
+
+    /// Gets the entry for a key of this `Value` as an `Object`.
+    /// Will return an `AccessError::NotAnObject` if called
+    /// on a `Value` that isn't an object - otherwise will
+    /// behave the same as `HashMap::entry`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an object.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn entry<K>(
+        &mut self,
+        k: K,
+    ) -> std::result::Result<
+        ObjectEntry<'_, <<Self as ValueAsMutContainer>::Object as ObjectMut>::VacantEntry<'_>>,
+        AccessError,
+    >
+    where
+        Self::Key: From<K> + Hash + Eq,
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Object: ObjectMut<Key = Self::Key, Element = Self::Target>,
+    {
+        self.as_object_mut()
+            .ok_or(AccessError::NotAnObject)
+            .map(|o| o.entry(k))
+    }
+
+    /// Iterates over the key value pairs of this `Value` as an `Object`
+    /// with mutable values.
+    /// Will return an `AccessError::NotAnObject` if called
+    /// on a `Value` that isn't an object
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an object.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn iter_object_mut(
+        &mut self,
+    ) -> std::result::Result<
+        <<Self as ValueAsMutContainer>::Object as ObjectMut>::IterMut<'_>,
+        AccessError,
+    >
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Object: ObjectMut<Key = Self::Key, Element = Self::Target>,
+    {
+        self.as_object_mut()
+            .ok_or(AccessError::NotAnObject)
+            .map(ObjectMut::iter_mut)
+    }
+
+    /// Iterates over the mutable values of this `Value` as an `Object`.
+    /// Will return an `AccessError::NotAnObject` if called
+    /// on a `Value` that isn't an object
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an object.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn values_mut(
+        &mut self,
+    ) -> std::result::Result<
+        <<Self as ValueAsMutContainer>::Object as ObjectMut>::ValuesMut<'_>,
+        AccessError,
+    >
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Object: ObjectMut<Key = Self::Key, Element = Self::Target>,
+    {
+        self.as_object_mut()
+            .ok_or(AccessError::NotAnObject)
+            .map(ObjectMut::values_mut)
+    }
+
+    /// Retains only the key value pairs of this `Value` as an `Object`
+    /// for which `f` returns `true`.
+    /// Will return an `AccessError::NotAnObject` if called
+    /// on a `Value` that isn't an object
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an object.
+    #[inline]
+    fn retain_object<F>(&mut self, f: F) -> std::result::Result<(), AccessError>
+    where
+        F: FnMut(&Self::Key, &mut Self::Target) -> bool,
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Object: ObjectMut<Key = Self::Key, Element = Self::Target>,
+    {
+        self.as_object_mut()
+            .ok_or(AccessError::NotAnObject)
+            .map(|o| o.retain(f))
+    }
+
+    /// Removes all key value pairs from this `Value` as an `Object`,
+    /// returning them as an iterator.
+    /// Will return an `AccessError::NotAnObject` if called
+    /// on a `Value` that isn't an object
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an object.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn drain(
+        &mut self,
+    ) -> std::result::Result<
+        <<Self as ValueAsMutContainer>::Object as ObjectMut>::Drain<'_>,
+        AccessError,
+    >
+    where
+        Self::Key: Sized,
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Object: ObjectMut<Key = Self::Key, Element = Self::Target>,
+    {
+        self.as_object_mut()
+            .ok_or(AccessError::NotAnObject)
+            .map(ObjectMut::drain)
+    }
+
+    /// Removes all key value pairs from this `Value` as an `Object`.
+    /// Will return an `AccessError::NotAnObject` if called
+    /// on a `Value` that isn't an object
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an object.
+    #[inline]
+    fn clear_object(&mut self) -> std::result::Result<(), AccessError>
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Object: ObjectMut<Key = Self::Key, Element = Self::Target>,
+    {
+        self.as_object_mut()
+            .ok_or(AccessError::NotAnObject)
+            .map(ObjectMut::clear)
+    }
 }
 /// `try_as_*` access to a value as an object
 pub trait ValueObjectTryAccess {
@@ -654,6 +793,176 @@ pub trait MutableArray {
 
     /// Same as `get_idx` but returns a mutable ref instead
     fn get_idx_mut(&mut self, i: usize) -> Option<&mut Self::Target>;
+
+    /// Inserts into this `Value` as an `Array` at index `i`.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array` - otherwise will
+    /// behave the same as `Vec::insert`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn insert_idx<V>(&mut self, i: usize, v: V) -> std::result::Result<(), AccessError>
+    where
+        V: Into<Self::Target>,
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(|a| a.insert(i, v.into()))
+    }
+
+    /// Removes the element at index `i` from this `Value` as an `Array`,
+    /// returns `None` if the index is out of bounds.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn remove_idx(&mut self, i: usize) -> std::result::Result<Option<Self::Target>, AccessError>
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(|a| a.remove(i))
+    }
+
+    /// Same as `remove_idx` but replaces the removed element with the
+    /// last one instead of shifting
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn swap_remove_idx(
+        &mut self,
+        i: usize,
+    ) -> std::result::Result<Option<Self::Target>, AccessError>
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(|a| a.swap_remove(i))
+    }
+
+    /// Truncates this `Value` as an `Array` to `len` elements.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array` - otherwise will
+    /// behave the same as `Vec::truncate`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn truncate(&mut self, len: usize) -> std::result::Result<(), AccessError>
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(|a| a.truncate(len))
+    }
+
+    /// Removes all elements from this `Value` as an `Array`.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn clear_array(&mut self) -> std::result::Result<(), AccessError>
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(ArrayMut::clear)
+    }
+
+    /// Appends all elements of `iter` to this `Value` as an `Array`.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn extend<I>(&mut self, iter: I) -> std::result::Result<(), AccessError>
+    where
+        I: IntoIterator<Item = Self::Target>,
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(|a| a.extend(iter))
+    }
+
+    /// Retains only the elements of this `Value` as an `Array` for
+    /// which `f` returns `true`.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn retain_array<F>(&mut self, f: F) -> std::result::Result<(), AccessError>
+    where
+        F: FnMut(&Self::Target) -> bool,
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(|a| a.retain(f))
+    }
+
+    /// Sorts this `Value` as an `Array` with the comparator `compare`.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    fn sort_by<F>(&mut self, compare: F) -> std::result::Result<(), AccessError>
+    where
+        F: FnMut(&Self::Target, &Self::Target) -> Ordering,
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(|a| a.sort_by(compare))
+    }
+
+    /// Iterates over mutable references to the elements of this `Value`
+    /// as an `Array`.
+    /// Will return an `AccessError::NotAnArray` if called
+    /// on a `Value` that isn't an `Array`
+    /// # Errors
+    ///
+    /// Will return `Err` if `self` is not an array.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn iter_array_mut(
+        &mut self,
+    ) -> std::result::Result<
+        <<Self as ValueAsMutContainer>::Array as ArrayMut>::IterMut<'_>,
+        AccessError,
+    >
+    where
+        Self: ValueAsMutContainer,
+        <Self as ValueAsMutContainer>::Array: ArrayMut<Element = Self::Target>,
+    {
+        self.as_array_mut()
+            .ok_or(AccessError::NotAnArray)
+            .map(ArrayMut::iter_mut)
+    }
 }
 
 /// Access to a value as an array with error handling
@@ -697,3 +1006,102 @@ pub trait ValueTryIntoContainer {
     /// if the requested type doesn't match the actual type
     fn try_into_object(self) -> Result<Self::Object, TryTypeError>;
 }
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+
+    /// Builds a document only through the `ValueBuilder` conversions
+    fn document<'input, V>() -> V
+    where
+        V: ValueBuilder<'input>,
+    {
+        let a = V::from(vec![
+            V::from(1_u8),
+            V::from(Some(V::from("s"))),
+            V::from(None),
+        ]);
+        let b: V = (0..3_u8).map(V::from).collect();
+        let o: V = [("a".to_string(), a), ("b".to_string(), b)]
+            .into_iter()
+            .collect();
+        let m = V::from(HashMap::from([("t".to_string(), V::from(true))]));
+        [o, m].into_iter().collect()
+    }
+
+    #[test]
+    fn builder_conversions() {
+        let v: NativeValue = document();
+        let o = v.get_idx(0).expect("object");
+        assert_eq!(o.get("a").and_then(|a| a.get_idx(0)?.as_u8()), Some(1));
+        assert_eq!(o.get("a").and_then(|a| a.get_idx(1)?.as_str()), Some("s"));
+        assert_eq!(
+            o.get("a")
+                .and_then(|a| a.get_idx(2))
+                .map(TypedScalarValue::is_null),
+            Some(true)
+        );
+        assert_eq!(o.get("b").map(Writable::encode).as_deref(), Some("[0,1,2]"));
+        assert_eq!(v.get_idx(1).and_then(|m| m.get_bool("t")), Some(true));
+
+        // a later pair replaces an earlier one with the same key
+        let o: NativeValue = [("a", 1), ("b", 2), ("a", 3)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), NativeValue::from(v)))
+            .collect();
+        assert_eq!(o.get_i64("a"), Some(3));
+        assert_eq!(o.as_object().map(HashMap::len), Some(2));
+    }
+
+    #[cfg(feature = "128bit")]
+    #[test]
+    fn builder_128bit() {
+        fn big<'input, V: ValueBuilder<'input>>() -> [V; 2] {
+            [V::from(-1_i128 << 100), V::from(1_u128 << 100)]
+        }
+        let [i, u]: [NativeValue; 2] = big();
+        assert_eq!(i.as_i128(), Some(-1 << 100));
+        assert_eq!(u.as_u128(), Some(1 << 100));
+    }
+
+    #[test]
+    fn mutable_array() {
+        let ints = |v: &NativeValue| -> Vec<u64> {
+            v.as_array()
+                .map(|a| a.iter().filter_map(ValueAsScalar::as_u64).collect())
+                .unwrap_or_default()
+        };
+        let mut v = NativeValue::from(Vec::<u64>::new());
+        v.extend([4_u64, 1, 3].map(NativeValue::from))
+            .expect("array");
+        v.insert_idx(1, 2_u64).expect("array");
+        assert_eq!(ints(&v), [4, 2, 1, 3]);
+        v.sort_by(|a, b| a.as_u64().cmp(&b.as_u64()))
+            .expect("array");
+        assert_eq!(ints(&v), [1, 2, 3, 4]);
+        assert_eq!(v.remove_idx(9), Ok(None));
+        assert_eq!(v.remove_idx(0), Ok(Some(NativeValue::from(1_u64))));
+        assert_eq!(v.swap_remove_idx(0), Ok(Some(NativeValue::from(2_u64))));
+        assert_eq!(ints(&v), [4, 3]);
+        v.retain_array(|e| e.as_u64() != Some(3)).expect("array");
+        for e in v.iter_array_mut().expect("array") {
+            *e = NativeValue::from(e.as_u64().map_or(0, |n| n * 10));
+        }
+        assert_eq!(ints(&v), [40]);
+        v.extend([5_u64, 6].map(NativeValue::from)).expect("array");
+        v.truncate(2).expect("array");
+        assert_eq!(ints(&v), [40, 5]);
+        v.clear_array().expect("array");
+        assert_eq!(v.as_array().map(Vec::len), Some(0));
+
+        let mut s = NativeValue::from("not an array");
+        assert_eq!(s.insert_idx(0, 1_u64), Err(AccessError::NotAnArray));
+        assert_eq!(s.remove_idx(0), Err(AccessError::NotAnArray));
+        assert_eq!(s.truncate(0), Err(AccessError::NotAnArray));
+        assert_eq!(s.clear_array(), Err(AccessError::NotAnArray));
+        assert!(s.iter_array_mut().is_err());
+        assert_eq!(s.as_str(), Some("not an array"));
+    }
+}
@@ -13,10 +13,27 @@ macro_rules! stry {
     };
 }
 
+use std::borrow::Borrow;
 use std::io;
 use std::io::Write;
 use std::ptr;
 
+use crate::{
+    array::Array,
+    base::{TypedValue, ValueAsContainer, ValueAsScalar},
+    object::Object,
+    ValueType,
+};
+
+#[cfg(feature = "async")]
+mod async_writer;
+#[cfg(feature = "async")]
+pub use async_writer::AsyncWriterGenerator;
+mod json5;
+mod ndjson;
+pub use json5::{Json5Config, Json5Generator, Json5Quote};
+pub use ndjson::NdjsonWriter;
+
 const QU: u8 = b'"';
 const BS: u8 = b'\\';
 const BB: u8 = b'b';
@@ -183,6 +200,70 @@ pub trait BaseGenerator {
         self.get_writer().write_all(s.as_bytes())
     }
 
+    /// writes a value, nested values are laid out using `new_line`,
+    /// `indent` and `dedent` so the result depends on the generator
+    /// # Errors
+    /// if the write fails
+    fn write_json<V>(&mut self, value: &V) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        match Node::of(value) {
+            Node::Null => self.write(b"null"),
+            Node::Bool(true) => self.write(b"true"),
+            Node::Bool(false) => self.write(b"false"),
+            Node::I64(n) => self.write_int(n),
+            Node::U64(n) => self.write_int(n),
+            Node::I128(n) => self.write_int(n),
+            Node::U128(n) => self.write_int(n),
+            Node::F64(n) => self.write_float(n),
+            Node::String(s) => self.write_string(s),
+            Node::Array(array) => {
+                let mut iter = array.iter();
+                let Some(first) = iter.next() else {
+                    return self.write(b"[]");
+                };
+                stry!(self.write_char(b'['));
+                self.indent();
+                stry!(self.new_line());
+                stry!(self.write_json(first));
+                for item in iter {
+                    stry!(self.write_char(b','));
+                    stry!(self.new_line());
+                    stry!(self.write_json(item));
+                }
+                self.dedent();
+                stry!(self.new_line());
+                self.write_char(b']')
+            }
+            Node::Object(object) => {
+                let mut iter = object.iter();
+                let Some((k, v)) = iter.next() else {
+                    return self.write(b"{}");
+                };
+                stry!(self.write_char(b'{'));
+                self.indent();
+                stry!(self.new_line());
+                stry!(self.write_string(k.borrow()));
+                stry!(self.write_min(b": ", b':'));
+                stry!(self.write_json(v));
+                for (k, v) in iter {
+                    stry!(self.write_char(b','));
+                    stry!(self.new_line());
+                    stry!(self.write_string(k.borrow()));
+                    stry!(self.write_min(b": ", b':'));
+                    stry!(self.write_json(v));
+                }
+                self.dedent();
+                stry!(self.new_line());
+                self.write_char(b'}')
+            }
+        }
+    }
+
     /// # Safety
     /// This function is unsafe because it may use simd instructions
     /// # Errors
@@ -643,6 +724,241 @@ where
     Ok(())
 }
 
+/// A borrowed view on a value that drives the generators, this is
+/// resolved through `value_type` so it works for every implementation
+pub(crate) enum Node<'v, V>
+where
+    V: ValueAsContainer,
+{
+    Null,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F64(f64),
+    String(&'v str),
+    Array(&'v V::Array),
+    Object(&'v V::Object),
+}
+
+// deriving would require `V: Copy`
+impl<V> Clone for Node<'_, V>
+where
+    V: ValueAsContainer,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for Node<'_, V> where V: ValueAsContainer {}
+
+impl<'v, V> Node<'v, V>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+{
+    pub(crate) fn of(value: &'v V) -> Self {
+        let node = match value.value_type() {
+            ValueType::Null => Some(Node::Null),
+            ValueType::Bool => value.as_bool().map(Node::Bool),
+            ValueType::I64 => value.as_i64().map(Node::I64),
+            ValueType::U64 => value.as_u64().map(Node::U64),
+            ValueType::I128 => value.as_i128().map(Node::I128),
+            ValueType::U128 => value.as_u128().map(Node::U128),
+            ValueType::F64 => value.as_f64().map(Node::F64),
+            ValueType::String => value.as_str().map(Node::String),
+            ValueType::Array => value.as_array().map(Node::Array),
+            ValueType::Object => value.as_object().map(Node::Object),
+            _ => None,
+        };
+        // Extended and custom types have no JSON representation of their
+        // own so we fall back to whatever accessor they support.
+        node.unwrap_or_else(|| Self::probe(value))
+    }
+
+    fn probe(value: &'v V) -> Self {
+        if let Some(b) = value.as_bool() {
+            Node::Bool(b)
+        } else if let Some(n) = value.as_i64() {
+            Node::I64(n)
+        } else if let Some(n) = value.as_u64() {
+            Node::U64(n)
+        } else if let Some(n) = value.as_i128() {
+            Node::I128(n)
+        } else if let Some(n) = value.as_u128() {
+            Node::U128(n)
+        } else if let Some(n) = value.as_f64() {
+            Node::F64(n)
+        } else if let Some(s) = value.as_str() {
+            Node::String(s)
+        } else if let Some(a) = value.as_array() {
+            Node::Array(a)
+        } else if let Some(o) = value.as_object() {
+            Node::Object(o)
+        } else {
+            Node::Null
+        }
+    }
+}
+
+/// Returns the number of bytes the `DumpGenerator` would produce for
+/// the value, without allocating.
+#[must_use]
+pub fn encoded_len<V>(value: &V) -> usize
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    json_len(value, None, 0)
+}
+
+/// Returns the number of bytes the `PrettyGenerator` would produce for
+/// the value with `spaces` per indentation, without allocating.
+#[must_use]
+pub fn encoded_len_pretty<V>(value: &V, spaces: u16) -> usize
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    json_len(value, Some(usize::from(spaces)), 0)
+}
+
+fn json_len<V>(value: &V, spaces: Option<usize>, dent: usize) -> usize
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    // a new line in the pretty generators is the line break and the indentation
+    let new_line = |dent: usize| spaces.map_or(0, |spaces| 1 + dent * spaces);
+    match Node::of(value) {
+        Node::Null | Node::Bool(true) => 4,
+        Node::Bool(false) => 5,
+        Node::I64(n) => itoa::Buffer::new().format(n).len(),
+        Node::U64(n) => itoa::Buffer::new().format(n).len(),
+        Node::I128(n) => itoa::Buffer::new().format(n).len(),
+        Node::U128(n) => itoa::Buffer::new().format(n).len(),
+        Node::F64(n) => ryu::Buffer::new().format_finite(n).len(),
+        Node::String(s) => string_len(s),
+        Node::Array(array) if array.is_empty() => 2,
+        Node::Array(array) => {
+            let items: usize = array
+                .iter()
+                .map(|v| json_len(v, spaces, dent + 1) + new_line(dent + 1))
+                .sum();
+            // brackets, separators and the closing line
+            2 + (array.len() - 1) + items + new_line(dent)
+        }
+        Node::Object(object) if object.is_empty() => 2,
+        Node::Object(object) => {
+            let colon = if spaces.is_some() { 2 } else { 1 };
+            let items: usize = object
+                .iter()
+                .map(|(k, v)| {
+                    string_len(k.borrow())
+                        + colon
+                        + json_len(v, spaces, dent + 1)
+                        + new_line(dent + 1)
+                })
+                .sum();
+            2 + (object.len() - 1) + items + new_line(dent)
+        }
+    }
+}
+
+/// Length of a string once quoted and escaped
+#[inline]
+fn string_len(string: &str) -> usize {
+    let string = string.as_bytes();
+    string.len() + 2 + escaped_extra_len(string)
+}
+
+/// Number of bytes escaping adds for a character, `\uXXXX` escapes add five
+/// and the short ones like `\n` add one.
+#[inline]
+fn escape_extra(ch: u8) -> usize {
+    match ESCAPED[ch as usize] {
+        0 => 0,
+        b'u' => 5,
+        _ => 1,
+    }
+}
+
+/// Number of extra bytes needed to escape the string
+#[inline]
+fn escaped_extra_len(string: &[u8]) -> usize {
+    #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+    {
+        if cfg!(target_feature = "avx2")
+            || (cfg!(feature = "runtime-detection") && std::is_x86_feature_detected!("avx2"))
+        {
+            return unsafe { escaped_extra_len_avx2(string) };
+        }
+    }
+    escaped_extra_len_rust(string)
+}
+
+#[inline]
+fn escaped_extra_len_rust(string: &[u8]) -> usize {
+    string.iter().map(|ch| escape_extra(*ch)).sum()
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+#[target_feature(enable = "avx2")]
+#[inline]
+/// Counts the extra bytes escaping needs with simd-acceleration
+/// # Safety
+/// This function is unsafe because it uses simd instructions
+unsafe fn escaped_extra_len_avx2(string: &[u8]) -> usize {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::{
+        __m256i, _mm256_and_si256, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_movemask_epi8,
+        _mm256_or_si256, _mm256_set1_epi8, _mm256_xor_si256,
+    };
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::{
+        __m256i, _mm256_and_si256, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_movemask_epi8,
+        _mm256_or_si256, _mm256_set1_epi8, _mm256_xor_si256,
+    };
+
+    let mut idx = 0;
+    let mut extra = 0;
+    let zero = _mm256_set1_epi8(0);
+    let lower_quote_range = _mm256_set1_epi8(0x1F_i8);
+    #[allow(clippy::cast_possible_wrap)] // it's a const, it's fine
+    let quote = _mm256_set1_epi8(b'"' as i8);
+    #[allow(clippy::cast_possible_wrap)] // it's a const, it's fine
+    let backslash = _mm256_set1_epi8(b'\\' as i8);
+    while string.len() - idx >= 32 {
+        // Load 32 bytes of data; _mm256_loadu_si256 does not require alignment
+        #[allow(clippy::cast_ptr_alignment)]
+        let data: __m256i = _mm256_loadu_si256(string.as_ptr().add(idx).cast::<__m256i>());
+        // Same test as in `write_str_simd_avx2`, see there for details.
+        let bs_or_quote = _mm256_or_si256(
+            _mm256_cmpeq_epi8(data, backslash),
+            _mm256_cmpeq_epi8(data, quote),
+        );
+        let in_quote_range = _mm256_and_si256(data, lower_quote_range);
+        let is_unchanged = _mm256_xor_si256(data, in_quote_range);
+        let in_range = _mm256_cmpeq_epi8(is_unchanged, zero);
+        #[allow(clippy::cast_sign_loss)] // we only use this as a bitmask
+        let mut quote_bits = _mm256_movemask_epi8(_mm256_or_si256(bs_or_quote, in_range)) as u32;
+        while quote_bits != 0 {
+            let quote_dist = quote_bits.trailing_zeros() as usize;
+            extra += escape_extra(*string.get_unchecked(idx + quote_dist));
+            quote_bits &= quote_bits - 1;
+        }
+        idx += 32;
+    }
+    extra + escaped_extra_len_rust(string.get_unchecked(idx..))
+}
+
 ///  Simple dump Generator
 pub struct DumpGenerator {
     code: Vec<u8>,
@@ -856,6 +1172,141 @@ where
     }
 }
 
+/// Error raised by the `SliceGenerator` when the output buffer is exhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferFull;
+
+impl std::fmt::Display for BufferFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The output buffer is full")
+    }
+}
+
+impl std::error::Error for BufferFull {}
+
+impl From<BufferFull> for io::Error {
+    fn from(e: BufferFull) -> Self {
+        io::Error::new(io::ErrorKind::WriteZero, e)
+    }
+}
+
+/// Returns true if the IO error was caused by a full output buffer
+#[must_use]
+pub fn is_buffer_full(e: &io::Error) -> bool {
+    matches!(e.get_ref(), Some(inner) if inner.is::<BufferFull>())
+}
+
+/// Writer over a fixed, caller provided buffer
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates a new writer over the given buffer
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceWriter { buf, len: 0 }
+    }
+
+    /// Number of bytes written so far
+    #[must_use]
+    pub fn written(&self) -> usize {
+        self.len
+    }
+
+    /// Number of bytes still available in the buffer
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.len
+    }
+
+    /// Returns the bytes written so far
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Write for SliceWriter<'_> {
+    #[inline]
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len().min(self.remaining());
+        if n == 0 && !data.is_empty() {
+            return Err(BufferFull.into());
+        }
+        self.buf[self.len..self.len + n].copy_from_slice(&data[..n]);
+        self.len += n;
+        Ok(n)
+    }
+
+    // We never write partial data so the output stays well formed up to the
+    // last successful write.
+    #[inline]
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        if data.len() > self.remaining() {
+            return Err(BufferFull.into());
+        }
+        self.buf[self.len..self.len + data.len()].copy_from_slice(data);
+        self.len += data.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Generator writing into a caller provided buffer without allocating
+pub struct SliceGenerator<'a> {
+    writer: SliceWriter<'a>,
+}
+
+impl<'a> SliceGenerator<'a> {
+    /// Creates a new generator
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceGenerator {
+            writer: SliceWriter::new(buf),
+        }
+    }
+
+    /// Number of bytes written so far
+    #[must_use]
+    pub fn written(&self) -> usize {
+        self.writer.written()
+    }
+
+    /// Returns the bytes written so far
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.writer.as_bytes()
+    }
+}
+
+impl<'a> BaseGenerator for SliceGenerator<'a> {
+    type T = SliceWriter<'a>;
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut SliceWriter<'a> {
+        &mut self.writer
+    }
+
+    #[inline]
+    fn write_char(&mut self, ch: u8) -> io::Result<()> {
+        if self.writer.remaining() == 0 {
+            return Err(BufferFull.into());
+        }
+        self.writer.buf[self.writer.len] = ch;
+        self.writer.len += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_min(&mut self, _: &[u8], min: u8) -> io::Result<()> {
+        self.write_char(min)
+    }
+}
+
 // From: https://github.com/dtolnay/fastwrite/blob/master/src/lib.rs#L68
 //
 // LLVM is not able to lower `Vec::extend_from_slice` into a memcpy, so this
@@ -873,3 +1324,114 @@ pub(crate) fn extend_from_slice(dst: &mut Vec<u8>, src: &[u8]) {
         dst.set_len(dst_len + src_len);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        encoded_len, encoded_len_pretty, escaped_extra_len, escaped_extra_len_rust, is_buffer_full,
+        BaseGenerator, DumpGenerator, PrettyGenerator, SliceGenerator,
+    };
+    use crate::{prelude::*, NativeValue};
+    use std::collections::HashMap;
+
+    fn document() -> NativeValue {
+        let mut object = HashMap::new();
+        object.insert("quote\"d".to_string(), NativeValue::from("tab\t ☃ \u{1}"));
+        object.insert("empty".to_string(), NativeValue::from(Vec::<u64>::new()));
+        object.insert(
+            "nested".to_string(),
+            NativeValue::from(HashMap::<String, NativeValue>::new()),
+        );
+        NativeValue::from(vec![
+            NativeValue::null(),
+            NativeValue::from(true),
+            NativeValue::from(i64::MIN),
+            NativeValue::from(u64::MAX),
+            NativeValue::from(-0.5),
+            NativeValue::from(1e300),
+            NativeValue::from(object),
+            NativeValue::from(vec![vec![1_u64], vec![]]),
+        ])
+    }
+
+    #[test]
+    fn slice_generator() {
+        let value = document();
+        let expected = value.encode();
+        let mut buf = [0_u8; 512];
+        let mut g = SliceGenerator::new(&mut buf);
+        g.write_json(&value).expect("fits");
+        assert_eq!(g.written(), expected.len());
+        assert_eq!(g.as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn slice_generator_full() {
+        let value = document();
+        let expected = value.encode();
+        for len in [0, 1, expected.len() / 2, expected.len() - 1] {
+            let mut buf = vec![0_u8; len];
+            let mut g = SliceGenerator::new(&mut buf);
+            let e = g.write_json(&value).expect_err("buffer too small");
+            assert!(is_buffer_full(&e));
+            // everything written so far is a prefix of the full output
+            assert!(expected.as_bytes().starts_with(g.as_bytes()));
+        }
+        let mut buf = vec![0_u8; expected.len()];
+        let mut g = SliceGenerator::new(&mut buf);
+        g.write_json(&value).expect("exact fit");
+        assert_eq!(g.as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn encoded_len_matches() {
+        let values = [
+            document(),
+            NativeValue::null(),
+            NativeValue::from("\"\\\n\r\u{7f}\u{1f} ünïcödé"),
+            NativeValue::from(-12_i64),
+            NativeValue::from(0.1),
+            NativeValue::from(f64::MAX),
+            NativeValue::from(Vec::<u64>::new()),
+            NativeValue::from(vec![NativeValue::from(vec![NativeValue::null()])]),
+        ];
+        for value in &values {
+            assert_eq!(encoded_len(value), value.encode().len());
+            assert_eq!(encoded_len_pretty(value, 2), value.encode_pp().len());
+            for spaces in [0, 1, 4, 8] {
+                let mut g = PrettyGenerator::new(spaces);
+                g.write_json(value).expect("write to string");
+                assert_eq!(encoded_len_pretty(value, spaces), g.consume().len());
+            }
+        }
+    }
+
+    #[test]
+    fn escaped_len_long_strings() {
+        // escapes and control bytes right before, on and after the 32 byte
+        // chunk boundaries and at the very end of the tail
+        for len in [31, 32, 33, 63, 64, 65, 95, 96, 100] {
+            for special in ["\"", "\\", "\n", "\u{1}", "\u{1f}", "\u{7f}", "ü"] {
+                let mut s = "a".repeat(len);
+                for at in [0, 31, 32, 63, 64, len - 1] {
+                    if at < len && s.is_char_boundary(at) && s.is_char_boundary(at + 1) {
+                        s.replace_range(at..=at, special);
+                    }
+                }
+                let bytes = s.as_bytes();
+                let extra = escaped_extra_len_rust(bytes);
+                assert_eq!(escaped_extra_len(bytes), extra, "{s:?}");
+                #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+                if std::is_x86_feature_detected!("avx2") {
+                    assert_eq!(unsafe { super::escaped_extra_len_avx2(bytes) }, extra);
+                }
+                let value = NativeValue::from(s.as_str());
+                let mut g = DumpGenerator::new();
+                g.write_json(&value).expect("write to string");
+                let json = g.consume();
+                assert_eq!(json.len(), bytes.len() + 2 + extra, "{s:?}");
+                assert_eq!(encoded_len(&value), json.len());
+            }
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use std::borrow::Borrow;
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+
+use futures_io::AsyncWrite;
+
+use super::{extend_from_slice, BaseGenerator, Node};
+use crate::{
+    array::Array,
+    base::{TypedValue, ValueAsContainer, ValueAsScalar},
+    object::Object,
+};
+
+/// Default number of bytes buffered before they are written out
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Pending containers while writing a value with the iterator over their
+/// remaining elements and if the next element is the first one. The future
+/// of `write_value` is `Send` if these iterators are.
+enum Frame<'v, V>
+where
+    V: ValueAsContainer + 'v,
+{
+    Array(<V::Array as Array>::Iter<'v>, bool),
+    Object(<V::Object as Object>::Iter<'v>, bool),
+}
+
+/// Async Writer Generator for `futures::AsyncWrite`, the output is buffered
+/// and written out in chunks.
+///
+/// `tokio::io::AsyncWrite` is not supported directly, tokio writers can be
+/// wrapped with `compat_write` from `tokio_util::compat::TokioAsyncWriteCompatExt`.
+pub struct AsyncWriterGenerator<'w, W>
+where
+    W: 'w + AsyncWrite + Unpin,
+{
+    writer: &'w mut W,
+    code: Vec<u8>,
+    chunk_size: usize,
+}
+
+impl<'w, W> AsyncWriterGenerator<'w, W>
+where
+    W: 'w + AsyncWrite + Unpin,
+{
+    /// Creates a new generator
+    pub fn new(writer: &'w mut W) -> Self {
+        Self::with_chunk_size(writer, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a new generator that writes out data every `chunk_size` bytes
+    pub fn with_chunk_size(writer: &'w mut W, chunk_size: usize) -> Self {
+        AsyncWriterGenerator {
+            writer,
+            code: Vec::with_capacity(chunk_size),
+            chunk_size,
+        }
+    }
+
+    /// Writes a value to the writer
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error is encountered
+    pub async fn write_value<V>(&mut self, value: &V) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        let mut stack: Vec<Frame<'_, V>> = Vec::new();
+        let mut next = Some(value);
+        loop {
+            if let Some(value) = next.take() {
+                match Node::of(value) {
+                    Node::Array(array) if !array.is_empty() => {
+                        self.write_char(b'[')?;
+                        stack.push(Frame::Array(array.iter(), true));
+                    }
+                    Node::Object(object) if !object.is_empty() => {
+                        self.write_char(b'{')?;
+                        stack.push(Frame::Object(object.iter(), true));
+                    }
+                    _ => self.write_json(value)?,
+                }
+            }
+            match stack.last_mut() {
+                None => break,
+                Some(Frame::Array(items, first)) => {
+                    if let Some(item) = items.next() {
+                        if !std::mem::take(first) {
+                            self.write_char(b',')?;
+                        }
+                        next = Some(item);
+                    } else {
+                        self.write_char(b']')?;
+                        stack.pop();
+                    }
+                }
+                Some(Frame::Object(entries, first)) => {
+                    if let Some((k, v)) = entries.next() {
+                        if !std::mem::take(first) {
+                            self.write_char(b',')?;
+                        }
+                        self.write_string(k.borrow())?;
+                        self.write_char(b':')?;
+                        next = Some(v);
+                    } else {
+                        self.write_char(b'}')?;
+                        stack.pop();
+                    }
+                }
+            }
+            if self.code.len() >= self.chunk_size {
+                self.write_buffer().await?;
+            }
+        }
+        self.write_buffer().await
+    }
+
+    /// Writes out all buffered data and flushes the writer
+    ///
+    /// Bytes are removed from the buffer as soon as the writer accepts them,
+    /// after an error or a dropped future calling `flush` again continues
+    /// with the data that wasn't written yet.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error is encountered
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.write_buffer().await?;
+        let writer = &mut *self.writer;
+        poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx)).await
+    }
+
+    async fn write_buffer(&mut self) -> io::Result<()> {
+        let writer = &mut *self.writer;
+        let code = &mut self.code;
+        while !code.is_empty() {
+            let n = poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, code)).await?;
+            if n == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+            code.drain(..n);
+        }
+        Ok(())
+    }
+}
+
+impl<'w, W> BaseGenerator for AsyncWriterGenerator<'w, W>
+where
+    W: 'w + AsyncWrite + Unpin,
+{
+    type T = Vec<u8>;
+
+    #[inline]
+    fn write(&mut self, slice: &[u8]) -> io::Result<()> {
+        extend_from_slice(&mut self.code, slice);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, ch: u8) -> io::Result<()> {
+        self.code.push(ch);
+        Ok(())
+    }
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut Vec<u8> {
+        &mut self.code
+    }
+
+    #[inline]
+    fn write_min(&mut self, _: &[u8], min: u8) -> io::Result<()> {
+        self.code.push(min);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AsyncWriterGenerator;
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use futures_io::AsyncWrite;
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    /// In-memory pipe that takes at most three bytes per write and is
+    /// pending on every other poll, like a slow socket.
+    #[derive(Default)]
+    struct Pipe {
+        data: Vec<u8>,
+        ready: bool,
+        flushed: bool,
+    }
+
+    impl Pipe {
+        fn poll_ready(&mut self, cx: &Context<'_>) -> Poll<()> {
+            self.ready = !self.ready;
+            if self.ready {
+                Poll::Ready(())
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    impl AsyncWrite for Pipe {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.poll_ready(cx).map(|()| {
+                let n = buf.len().min(3);
+                self.data.extend_from_slice(&buf[..n]);
+                Ok(n)
+            })
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.poll_ready(cx).map(|()| {
+                self.flushed = true;
+                Ok(())
+            })
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.poll_flush(cx)
+        }
+    }
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    fn document() -> NativeValue {
+        let mut inner = HashMap::new();
+        inner.insert("a\"b".to_string(), NativeValue::from(vec![1_u64, 2, 3]));
+        inner.insert("empty".to_string(), NativeValue::from(Vec::<u64>::new()));
+        inner.insert("none".to_string(), NativeValue::null());
+        let items = vec![
+            NativeValue::from("snow ☃\n"),
+            NativeValue::from(-1.5),
+            NativeValue::from(inner),
+            NativeValue::from(HashMap::<String, NativeValue>::new()),
+            NativeValue::from(vec![vec![true], vec![false]]),
+        ];
+        NativeValue::from(items)
+    }
+
+    #[test]
+    fn matches_encode() {
+        let value = document();
+        for chunk_size in [1, 4, 1024] {
+            let mut pipe = Pipe::default();
+            block_on(async {
+                let mut generator = AsyncWriterGenerator::with_chunk_size(&mut pipe, chunk_size);
+                generator.write_value(&value).await?;
+                generator.flush().await
+            })
+            .expect("write to pipe");
+            assert!(pipe.flushed);
+            assert_eq!(String::from_utf8(pipe.data).expect("utf8"), value.encode());
+        }
+    }
+
+    #[test]
+    fn scalars_and_empty_containers() {
+        for value in [
+            NativeValue::null(),
+            NativeValue::from(42_u64),
+            NativeValue::from(Vec::<u64>::new()),
+            NativeValue::from(HashMap::<String, NativeValue>::new()),
+        ] {
+            let mut pipe = Pipe::default();
+            block_on(AsyncWriterGenerator::new(&mut pipe).write_value(&value))
+                .expect("write to pipe");
+            assert_eq!(String::from_utf8(pipe.data).expect("utf8"), value.encode());
+        }
+    }
+
+    #[test]
+    fn write_zero() {
+        struct Closed;
+        impl AsyncWrite for Closed {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                _: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(Ok(0))
+            }
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+            fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+        let err = block_on(AsyncWriterGenerator::new(&mut Closed).write_value(&document()))
+            .expect_err("closed writer");
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn resume_after_error() {
+        /// Takes three bytes per write and fails the second write
+        #[derive(Default)]
+        struct Flaky {
+            data: Vec<u8>,
+            writes: usize,
+        }
+        impl AsyncWrite for Flaky {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.writes += 1;
+                if self.writes == 2 {
+                    return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+                }
+                let n = buf.len().min(3);
+                self.data.extend_from_slice(&buf[..n]);
+                Poll::Ready(Ok(n))
+            }
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+            fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+        let value = document();
+        let mut flaky = Flaky::default();
+        let mut generator = AsyncWriterGenerator::new(&mut flaky);
+        let err = block_on(generator.write_value(&value)).expect_err("second write fails");
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        block_on(generator.flush()).expect("retry");
+        assert_eq!(String::from_utf8(flaky.data).expect("utf8"), value.encode());
+    }
+
+    #[test]
+    fn resume_after_drop() {
+        let value = document();
+        let mut pipe = Pipe::default();
+        let mut generator = AsyncWriterGenerator::new(&mut pipe);
+        {
+            let waker = Waker::from(Arc::new(Noop));
+            let mut cx = Context::from_waker(&waker);
+            let mut write = Box::pin(generator.write_value(&value));
+            // the pipe takes three bytes and then is pending
+            assert!(write.as_mut().poll(&mut cx).is_pending());
+        }
+        block_on(generator.flush()).expect("retry");
+        assert_eq!(String::from_utf8(pipe.data).expect("utf8"), value.encode());
+    }
+}
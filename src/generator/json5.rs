@@ -0,0 +1,369 @@
+use std::borrow::Borrow;
+use std::io;
+
+use super::{extend_from_slice, BaseGenerator, Node};
+use crate::{
+    array::Array,
+    base::{TypedValue, ValueAsContainer, ValueAsScalar},
+    object::Object,
+};
+
+/// Quote character used for JSON5 strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Json5Quote {
+    /// `"string"`
+    #[default]
+    Double,
+    /// `'string'`
+    Single,
+}
+
+/// Syntax choices for the `Json5Generator`, the default produces plain
+/// JSON which is valid JSON5 as well
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(clippy::struct_excessive_bools)] // they are independent switches
+pub struct Json5Config {
+    /// Write object keys that are valid identifiers without quotes
+    pub unquoted_keys: bool,
+    /// Quote character for strings and quoted keys
+    pub quote: Json5Quote,
+    /// Add a trailing comma after the last element of arrays and objects,
+    /// this only applies when pretty printing
+    pub trailing_commas: bool,
+    /// Write integers as hexadecimal (`0xff`)
+    pub hex_integers: bool,
+    /// Write non finite floats as `Infinity`, `-Infinity` and `NaN`,
+    /// otherwise they are written as `null`
+    pub non_finite_floats: bool,
+}
+
+/// JSON5 Generator
+pub struct Json5Generator {
+    code: Vec<u8>,
+    dent: u16,
+    spaces_per_indent: u16,
+    config: Json5Config,
+}
+
+impl Json5Generator {
+    /// Creates a new generator producing compact output
+    #[must_use]
+    pub fn new(config: Json5Config) -> Self {
+        Self::pretty(0, config)
+    }
+
+    /// Creates a new pretty printing generator, `0` spaces produce compact
+    /// output
+    #[must_use]
+    pub fn pretty(spaces: u16, config: Json5Config) -> Self {
+        Self {
+            code: Vec::with_capacity(1024),
+            dent: 0,
+            spaces_per_indent: spaces,
+            config,
+        }
+    }
+
+    /// Returns the data as a String
+    #[must_use]
+    pub fn consume(self) -> String {
+        unsafe { String::from_utf8_unchecked(self.code) }
+    }
+
+    fn is_pretty(&self) -> bool {
+        self.spaces_per_indent > 0
+    }
+
+    /// Writes a value
+    ///
+    /// # Errors
+    ///
+    /// if the write fails
+    pub fn write_value<V>(&mut self, value: &V) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        match Node::of(value) {
+            Node::I64(n) if self.config.hex_integers => {
+                self.write_hex(n < 0, u128::from(n.unsigned_abs()))
+            }
+            Node::U64(n) if self.config.hex_integers => self.write_hex(false, u128::from(n)),
+            Node::I128(n) if self.config.hex_integers => self.write_hex(n < 0, n.unsigned_abs()),
+            Node::U128(n) if self.config.hex_integers => self.write_hex(false, n),
+            Node::Array(array) if !array.is_empty() => {
+                self.write_char(b'[')?;
+                self.indent();
+                for (i, item) in array.iter().enumerate() {
+                    if i > 0 {
+                        self.write_char(b',')?;
+                    }
+                    self.new_line()?;
+                    self.write_value(item)?;
+                }
+                self.close(b']')
+            }
+            Node::Object(object) if !object.is_empty() => {
+                self.write_char(b'{')?;
+                self.indent();
+                for (i, (k, v)) in object.iter().enumerate() {
+                    if i > 0 {
+                        self.write_char(b',')?;
+                    }
+                    self.new_line()?;
+                    self.write_key(k.borrow())?;
+                    self.write_min(b": ", b':')?;
+                    self.write_value(v)?;
+                }
+                self.close(b'}')
+            }
+            _ => self.write_json(value),
+        }
+    }
+
+    fn close(&mut self, end: u8) -> io::Result<()> {
+        if self.config.trailing_commas && self.is_pretty() {
+            self.write_char(b',')?;
+        }
+        self.dedent();
+        self.new_line()?;
+        self.write_char(end)
+    }
+
+    fn write_key(&mut self, key: &str) -> io::Result<()> {
+        if self.config.unquoted_keys && is_identifier(key) {
+            self.write(key.as_bytes())
+        } else {
+            self.write_string(key)
+        }
+    }
+
+    fn write_hex(&mut self, negative: bool, mut n: u128) -> io::Result<()> {
+        static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+        // sign, prefix and 32 digits for a u128
+        let mut buf = [0_u8; 35];
+        let mut start = buf.len();
+        loop {
+            start -= 1;
+            buf[start] = HEX_DIGITS[(n & 0xF) as usize];
+            n >>= 4;
+            if n == 0 {
+                break;
+            }
+        }
+        start -= 2;
+        buf[start..start + 2].copy_from_slice(b"0x");
+        if negative {
+            start -= 1;
+            buf[start] = b'-';
+        }
+        self.write(&buf[start..])
+    }
+}
+
+/// Checks if a key can be written as an (ASCII) `ECMAScript` identifier,
+/// non ASCII keys are always quoted.
+fn is_identifier(key: &str) -> bool {
+    let mut bytes = key.bytes();
+    bytes
+        .next()
+        .map_or(false, |b| b.is_ascii_alphabetic() || b == b'_' || b == b'$')
+        && bytes.all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'$')
+}
+
+impl BaseGenerator for Json5Generator {
+    type T = Vec<u8>;
+
+    #[inline]
+    fn write(&mut self, slice: &[u8]) -> io::Result<()> {
+        extend_from_slice(&mut self.code, slice);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, ch: u8) -> io::Result<()> {
+        self.code.push(ch);
+        Ok(())
+    }
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut Vec<u8> {
+        &mut self.code
+    }
+
+    #[inline]
+    fn write_min(&mut self, slice: &[u8], min: u8) -> io::Result<()> {
+        if self.is_pretty() {
+            extend_from_slice(&mut self.code, slice);
+        } else {
+            self.code.push(min);
+        }
+        Ok(())
+    }
+
+    fn write_string(&mut self, string: &str) -> io::Result<()> {
+        match self.config.quote {
+            Json5Quote::Double => {
+                self.write_char(b'"')?;
+                self.write_string_content(string)?;
+                self.write_char(b'"')
+            }
+            Json5Quote::Single => {
+                // The JSON escaping turns `"` into `\"` which is valid in
+                // JSON5 single quoted strings as well, so we only need to
+                // take care of the single quotes.
+                self.write_char(b'\'')?;
+                for (i, part) in string.split('\'').enumerate() {
+                    if i > 0 {
+                        self.write(b"\\'")?;
+                    }
+                    self.write_string_content(part)?;
+                }
+                self.write_char(b'\'')
+            }
+        }
+    }
+
+    fn write_float(&mut self, num: f64) -> io::Result<()> {
+        if num.is_finite() {
+            let mut buffer = ryu::Buffer::new();
+            self.write(buffer.format_finite(num).as_bytes())
+        } else if !self.config.non_finite_floats {
+            self.write(b"null")
+        } else if num.is_nan() {
+            self.write(b"NaN")
+        } else if num.is_sign_positive() {
+            self.write(b"Infinity")
+        } else {
+            self.write(b"-Infinity")
+        }
+    }
+
+    fn new_line(&mut self) -> io::Result<()> {
+        if self.is_pretty() {
+            self.code.push(b'\n');
+            self.code.resize(
+                self.code.len() + (self.dent * self.spaces_per_indent) as usize,
+                b' ',
+            );
+        }
+        Ok(())
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn dedent(&mut self) {
+        self.dent -= 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Json5Config, Json5Generator, Json5Quote};
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+
+    fn json5(value: &NativeValue, spaces: u16, config: Json5Config) -> String {
+        let mut g = Json5Generator::pretty(spaces, config);
+        g.write_value(value).expect("write to vec");
+        g.consume()
+    }
+
+    fn object(k: &str, v: NativeValue) -> NativeValue {
+        let mut map = HashMap::new();
+        map.insert(k.to_string(), v);
+        NativeValue::from(map)
+    }
+
+    #[test]
+    fn default_is_json() {
+        let value = NativeValue::from(vec![
+            object("a b", NativeValue::from("x\"y")),
+            NativeValue::from(-3_i64),
+            NativeValue::from(0.25),
+            NativeValue::from(Vec::<u64>::new()),
+        ]);
+        let config = Json5Config::default();
+        assert_eq!(json5(&value, 0, config), value.encode());
+        assert_eq!(json5(&value, 2, config), value.encode_pp());
+    }
+
+    #[test]
+    fn unquoted_keys() {
+        let config = Json5Config {
+            unquoted_keys: true,
+            ..Json5Config::default()
+        };
+        for (key, expected) in [
+            ("_id$1", "{_id$1:1}"),
+            ("1st", r#"{"1st":1}"#),
+            ("a-b", r#"{"a-b":1}"#),
+            ("", r#"{"":1}"#),
+            ("ü", r#"{"ü":1}"#),
+        ] {
+            let value = object(key, NativeValue::from(1_u64));
+            assert_eq!(json5(&value, 0, config), expected);
+        }
+    }
+
+    #[test]
+    fn single_quotes() {
+        let config = Json5Config {
+            quote: Json5Quote::Single,
+            ..Json5Config::default()
+        };
+        let value = object("it's", NativeValue::from("say \"hi\"\n"));
+        assert_eq!(json5(&value, 0, config), r#"{'it\'s':'say \"hi\"\n'}"#);
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let config = Json5Config {
+            trailing_commas: true,
+            ..Json5Config::default()
+        };
+        let value = NativeValue::from(vec![object("a", NativeValue::from(vec![1_u64]))]);
+        assert_eq!(
+            json5(&value, 2, config),
+            "[\n  {\n    \"a\": [\n      1,\n    ],\n  },\n]"
+        );
+        // compact output never has trailing commas
+        assert_eq!(json5(&value, 0, config), r#"[{"a":[1]}]"#);
+    }
+
+    #[test]
+    fn hex_integers() {
+        let config = Json5Config {
+            hex_integers: true,
+            ..Json5Config::default()
+        };
+        let value = NativeValue::from(vec![
+            NativeValue::from(0_u64),
+            NativeValue::from(255_u64),
+            NativeValue::from(-16_i64),
+            NativeValue::from(i64::MIN),
+            NativeValue::from(u64::MAX),
+            NativeValue::from(1.5),
+        ]);
+        assert_eq!(
+            json5(&value, 0, config),
+            "[0x0,0xff,-0x10,-0x8000000000000000,0xffffffffffffffff,1.5]"
+        );
+    }
+
+    #[test]
+    fn non_finite_floats() {
+        let value = NativeValue::from(vec![f64::NAN, f64::INFINITY, f64::NEG_INFINITY]);
+        let config = Json5Config {
+            non_finite_floats: true,
+            ..Json5Config::default()
+        };
+        assert_eq!(json5(&value, 0, config), "[NaN,Infinity,-Infinity]");
+        assert_eq!(json5(&value, 0, Json5Config::default()), "[null,null,null]");
+    }
+}
@@ -0,0 +1,225 @@
+use std::io::{self, Write};
+
+use crate::base::Writable;
+
+/// Writer for newline delimited JSON (JSON Lines), every value is written
+/// as a single compact record terminated by `\n`.
+///
+/// Records are encoded into one internal buffer that is reused between
+/// records, so writing a stream of values does not allocate per line.
+pub struct NdjsonWriter<W>
+where
+    W: Write,
+{
+    writer: W,
+    buf: Vec<u8>,
+    records: u64,
+    bytes: u64,
+    flush_every: u64,
+}
+
+impl<W> NdjsonWriter<W>
+where
+    W: Write,
+{
+    /// Creates a new writer
+    pub fn new(writer: W) -> Self {
+        NdjsonWriter {
+            writer,
+            buf: Vec::new(),
+            records: 0,
+            bytes: 0,
+            flush_every: 0,
+        }
+    }
+
+    /// Flushes the underlying writer after every `records` records,
+    /// `0` disables periodic flushing
+    #[must_use]
+    pub fn flush_every(mut self, records: u64) -> Self {
+        self.flush_every = records;
+        self
+    }
+
+    /// Writes a value as a single record
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error is encountered or if the compact
+    /// encoding of the value contains a new line, nothing is written then
+    pub fn write_value<V>(&mut self, value: &V) -> io::Result<()>
+    where
+        V: Writable,
+    {
+        self.buf.clear();
+        value.write(&mut self.buf)?;
+        // The generators escape new lines in strings but a custom `Writable`
+        // could still emit one, which would split the record.
+        if self.buf.contains(&b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "record contains a new line",
+            ));
+        }
+        self.buf.push(b'\n');
+        self.writer.write_all(&self.buf)?;
+        self.records += 1;
+        self.bytes += self.buf.len() as u64;
+        if self.flush_every > 0 && self.records % self.flush_every == 0 {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Number of records written so far
+    #[must_use]
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Number of bytes written so far, including the line terminators
+    #[must_use]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Flushes the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error is encountered
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Flushes and returns the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error is encountered
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NdjsonWriter;
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+    use std::io::{self, Write};
+
+    /// Writes its value pretty printed even when asked for the compact form
+    struct Pretty(NativeValue);
+
+    impl Writable for Pretty {
+        fn encode(&self) -> String {
+            self.0.encode_pp()
+        }
+        fn encode_pp(&self) -> String {
+            self.0.encode_pp()
+        }
+        fn write<'writer, W>(&self, w: &mut W) -> io::Result<()>
+        where
+            W: 'writer + Write,
+        {
+            self.0.write_pp(w)
+        }
+        fn write_pp<'writer, W>(&self, w: &mut W) -> io::Result<()>
+        where
+            W: 'writer + Write,
+        {
+            self.0.write_pp(w)
+        }
+    }
+
+    #[test]
+    fn records() {
+        let mut object = HashMap::new();
+        object.insert("line".to_string(), NativeValue::from("one\ntwo"));
+        let values = [
+            NativeValue::from(object),
+            NativeValue::from(vec![1_u64, 2]),
+            NativeValue::null(),
+        ];
+        let mut writer = NdjsonWriter::new(Vec::new());
+        for value in &values {
+            writer.write_value(value).expect("write record");
+        }
+        assert_eq!(writer.records(), 3);
+        let out = writer.into_inner().expect("flush");
+        assert_eq!(
+            String::from_utf8(out).expect("utf8"),
+            "{\"line\":\"one\\ntwo\"}\n[1,2]\nnull\n"
+        );
+    }
+
+    #[test]
+    fn bytes_written() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer
+            .write_value(&NativeValue::from("abc"))
+            .expect("write record");
+        writer
+            .write_value(&NativeValue::from(10_u64))
+            .expect("write record");
+        assert_eq!(writer.bytes_written(), 9);
+        assert_eq!(writer.bytes_written(), writer.get_ref().len() as u64);
+    }
+
+    #[test]
+    fn rejects_new_lines() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        let value = Pretty(NativeValue::from(vec![1_u64, 2]));
+        let err = writer
+            .write_value(&value)
+            .expect_err("record with new lines");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(writer.records(), 0);
+        assert!(writer.get_ref().is_empty());
+
+        // scalars have no pretty layout and go through
+        writer
+            .write_value(&Pretty(NativeValue::from(1_u64)))
+            .expect("write record");
+        assert_eq!(writer.get_ref().as_slice(), b"1\n");
+    }
+
+    #[test]
+    fn flush_every() {
+        struct Counting {
+            data: Vec<u8>,
+            flushes: usize,
+        }
+        impl Write for Counting {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.data.write(buf)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                self.flushes += 1;
+                Ok(())
+            }
+        }
+        let mut writer = NdjsonWriter::new(Counting {
+            data: Vec::new(),
+            flushes: 0,
+        })
+        .flush_every(2);
+        for i in 0..5_u64 {
+            writer
+                .write_value(&NativeValue::from(i))
+                .expect("write record");
+        }
+        assert_eq!(writer.get_ref().flushes, 2);
+        let inner = writer.into_inner().expect("flush");
+        assert_eq!(inner.flushes, 3);
+        assert_eq!(inner.data, b"0\n1\n2\n3\n4\n");
+    }
+}
@@ -14,9 +14,42 @@ use crate::{
         ValueTryIntoString,
     },
     object::{Object, ObjectMut},
-    AccessError, ExtendedValueType, TryTypeError, ValueType,
+    AccessError, ExtendedValueType, MutableValue, TryTypeError, Value, ValueType,
 };
 
+impl<T> Value for T
+where
+    T: TypedValue
+        + ValueAsScalar
+        + ValueIntoString
+        + ValueAsContainer
+        + ValueIntoContainer<
+            Array = <T as ValueAsContainer>::Array,
+            Object = <T as ValueAsContainer>::Object,
+        >,
+    <T as ValueAsContainer>::Array: Array<Element = T>,
+    <T as ValueAsContainer>::Object: Object<Element = T>,
+    <<T as ValueAsContainer>::Object as Object>::Key: Borrow<str> + Hash + Eq,
+{
+    type ValueArray = <T as ValueAsContainer>::Array;
+    type ValueObject = <T as ValueAsContainer>::Object;
+    type Key = <<T as ValueAsContainer>::Object as Object>::Key;
+}
+
+impl<T> MutableValue for T
+where
+    T: Value
+        + ValueAsMutContainer<
+            Array = <T as ValueAsContainer>::Array,
+            Object = <T as ValueAsContainer>::Object,
+        >,
+    <T as ValueAsContainer>::Array: ArrayMut<Element = T>,
+    <T as ValueAsContainer>::Object: ObjectMut<Key = <T as Value>::Key, Element = T>,
+{
+    type ValueArrayMut = <T as ValueAsContainer>::Array;
+    type ValueObjectMut = <T as ValueAsContainer>::Object;
+}
+
 impl<T> ValueTryIntoString for T
 where
     T: ValueIntoString + TypedValue,
@@ -910,3 +943,118 @@ where
         self.as_array_mut().and_then(|a| a.get_mut(i))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use crate::NativeValue;
+
+    const DOC: &str = r#"{"a":[1,null,{"b":-2.5,"c":null}],"d":"e","f":[[null]]}"#;
+
+    /// Node count, nesting depth, sum of all numbers and every key and string
+    #[derive(Debug, PartialEq)]
+    struct Summary {
+        nodes: usize,
+        depth: usize,
+        sum: f64,
+        strings: Vec<String>,
+    }
+
+    /// Walks a value only through the `Value` bound
+    fn summary<V: Value>(v: &V) -> Summary {
+        fn walk<V: Value>(v: &V, depth: usize, s: &mut Summary) {
+            s.nodes += 1;
+            s.depth = s.depth.max(depth);
+            if let Some(a) = v.as_array() {
+                for e in a.iter() {
+                    walk(e, depth + 1, s);
+                }
+            } else if let Some(o) = v.as_object() {
+                for (k, e) in o.iter() {
+                    s.strings.push(k.borrow().to_string());
+                    walk(e, depth + 1, s);
+                }
+            } else if let Some(n) = v.cast_f64() {
+                s.sum += n;
+            } else if let Some(str) = v.as_str() {
+                s.strings.push(str.to_string());
+            }
+        }
+        let mut s = Summary {
+            nodes: 0,
+            depth: 0,
+            sum: 0.0,
+            strings: Vec::new(),
+        };
+        walk(v, 0, &mut s);
+        s.strings.sort();
+        s
+    }
+
+    /// Removes nulls from all arrays and objects only through the
+    /// `MutableValue` bound, returns how many were removed
+    fn strip_nulls<V: MutableValue>(v: &mut V) -> usize {
+        let mut removed = 0;
+        if let Some(a) = v.as_array_mut() {
+            let mut i = 0;
+            while let Some(e) = a.get_mut(i) {
+                if e.is_null() {
+                    let _null = a.remove(i);
+                    removed += 1;
+                } else {
+                    removed += strip_nulls(e);
+                    i += 1;
+                }
+            }
+        } else if let Some(o) = v.as_object_mut() {
+            o.retain(|_, e| {
+                removed += strip_nulls(e);
+                let keep = !e.is_null();
+                removed += usize::from(!keep);
+                keep
+            });
+        }
+        removed
+    }
+
+    fn expected() -> Summary {
+        Summary {
+            nodes: 11,
+            depth: 3,
+            sum: -1.5,
+            strings: ["a", "b", "c", "d", "e", "f"].map(String::from).to_vec(),
+        }
+    }
+
+    #[test]
+    fn generic_native() {
+        let mut v: NativeValue = crate::parser::parse(DOC).expect("valid");
+        assert_eq!(summary(&v), expected());
+        assert_eq!(strip_nulls(&mut v), 3);
+        assert_eq!(summary(&v).nodes, 8);
+        assert_eq!(
+            v.get("a").map(Writable::encode).as_deref(),
+            Some(r#"[1,{"b":-2.5}]"#)
+        );
+        assert_eq!(v.get("f").map(Writable::encode).as_deref(), Some("[[]]"));
+        assert_eq!(strip_nulls(&mut v), 0);
+    }
+
+    #[cfg(feature = "arena")]
+    #[test]
+    fn generic_arena() {
+        let bump = bumpalo::Bump::new();
+        let b = crate::arena::ArenaBuilder::new(&bump);
+        let source: NativeValue = crate::parser::parse(DOC).expect("valid");
+        let mut v = b.copy_of(&source);
+        assert_eq!(summary(&v), expected());
+        assert_eq!(strip_nulls(&mut v), 3);
+        assert_eq!(summary(&v).nodes, 8);
+        assert_eq!(
+            v.get("a").map(Writable::encode).as_deref(),
+            Some(r#"[1,{"b":-2.5}]"#)
+        );
+        assert_eq!(v.get("f").map(Writable::encode).as_deref(), Some("[[]]"));
+        assert_eq!(strip_nulls(&mut v), 0);
+    }
+}
@@ -23,16 +23,22 @@ compile_error!(
     the `128bit` feature, and that they have been merged by Cargo."
 );
 
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::Hash;
 
 mod array;
+/// Conversions from values into rust types
+pub mod convert;
 /// Traits for serializing JSON
 pub mod generator;
 mod impls;
+mod native;
 mod node;
 mod object;
 mod option;
+mod pointer;
 /// Prelude for traits
 pub mod prelude;
 
@@ -42,7 +48,30 @@ pub mod base;
 /// Traits that have derived implementations relying on `base` traitsa
 pub mod derived;
 
+/// Object safe versions of the value and container traits with boxed iterators
+pub mod boxed;
+
+/// Arena allocated values
+#[cfg(feature = "arena")]
+pub mod arena;
+/// CBOR (RFC 8949) encoding and decoding
+pub mod cbor;
+/// `MessagePack` encoding and decoding
+pub mod msgpack;
+/// A simple JSON parser building any `ValueBuilder`
+pub mod parser;
+/// A flat tape representation of documents
+pub mod tape;
+/// A pull tokenizer for JSON
+pub mod tokenizer;
+/// TOML emitter
+pub mod toml;
+/// YAML emitter
+pub mod yaml;
+
+pub use native::NativeValue;
 pub use node::StaticNode;
+pub use object::{AssocList, VacantAssocEntry};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// An access error for `ValueType`
@@ -171,10 +200,26 @@ impl Default for ValueType {
     }
 }
 
+/// Conversion from 128 bit integers, only required with the `128bit` feature
+#[cfg(feature = "128bit")]
+pub trait From128: From<i128> + From<u128> {}
+#[cfg(feature = "128bit")]
+impl<T> From128 for T where T: From<i128> + From<u128> {}
+
+/// Conversion from 128 bit integers, only required with the `128bit` feature
+#[cfg(not(feature = "128bit"))]
+pub trait From128 {}
+#[cfg(not(feature = "128bit"))]
+impl<T> From128 for T {}
+
 #[allow(clippy::trait_duplication_in_bounds)] // This is a bug From<()> is counted as duplicate
 /// Support of builder methods for traits.
+///
+/// Besides scalars, values can be built from vectors, maps and iterators of
+/// values or `(String, value)` pairs; `None` is turned into null.
 pub trait ValueBuilder<'input>:
     Default
+    + From128
     + From<StaticNode>
     + From<i8>
     + From<i16>
@@ -191,6 +236,11 @@ pub trait ValueBuilder<'input>:
     + From<String>
     + From<&'input str>
     + From<Cow<'input, str>>
+    + From<Vec<Self>>
+    + From<HashMap<String, Self>>
+    + From<Option<Self>>
+    + FromIterator<Self>
+    + FromIterator<(String, Self)>
 {
     /// Returns an empty array with a given capacity
     fn array_with_capacity(capacity: usize) -> Self;
@@ -227,26 +277,48 @@ impl std::fmt::Display for TryTypeError {
 
 impl std::error::Error for TryTypeError {}
 
-// /// The `Value` exposes common interface for values, this allows using both/// `BorrowedValue` and `OwnedValue` nearly interchangeable
-// pub trait Value:
-//     Sized
-//     + Index<usize>
-//     + PartialEq<i8>
-//     + PartialEq<i16>
-//     + PartialEq<i32>
-//     + PartialEq<i64>
-//     + PartialEq<i128>
-//     + PartialEq<u8>
-//     + PartialEq<u16>
-//     + PartialEq<u32>
-//     + PartialEq<u64>
-//     + PartialEq<u128>
-//     + PartialEq<f32>
-//     + PartialEq<f64>
-//     + PartialEq<String>
-//     + PartialEq<bool>
-//     + PartialEq<()>
-//     + derived::ValueTryAsScalar
-//     + base::ValueAsContainer
-// {
-// }
+/// The `Value` exposes common interface for values, this allows using both
+/// `BorrowedValue` and `OwnedValue` nearly interchangeable.
+///
+/// Arrays and objects contain `Self` so recursive algorithms only need a
+/// single `V: Value` bound. It is implemented for every type that implements
+/// the base traits with matching containers.
+pub trait Value:
+    Sized
+    + base::TypedValue
+    + base::ValueAsScalar
+    + base::ValueIntoString
+    + base::ValueAsContainer<
+        Array = <Self as Value>::ValueArray,
+        Object = <Self as Value>::ValueObject,
+    > + base::ValueIntoContainer<
+        Array = <Self as Value>::ValueArray,
+        Object = <Self as Value>::ValueObject,
+    >
+{
+    /// The array structure
+    type ValueArray: array::Array<Element = Self>;
+    /// The object structure
+    type ValueObject: object::Object<Key = Self::Key, Element = Self>;
+    /// The key in objects
+    type Key: ?Sized + Borrow<str> + Hash + Eq;
+}
+
+/// A `Value` that can be mutated in place, arrays and objects contain `Self`.
+///
+/// It is implemented for every `Value` with mutable containers.
+pub trait MutableValue:
+    Value<
+        ValueArray = <Self as MutableValue>::ValueArrayMut,
+        ValueObject = <Self as MutableValue>::ValueObjectMut,
+    > + base::ValueAsMutContainer<
+        Array = <Self as MutableValue>::ValueArrayMut,
+        Object = <Self as MutableValue>::ValueObjectMut,
+    >
+{
+    /// The mutable array structure
+    type ValueArrayMut: array::Array<Element = Self> + array::ArrayMut<Element = Self>;
+    /// The mutable object structure
+    type ValueObjectMut: object::Object<Key = Self::Key, Element = Self>
+        + object::ObjectMut<Key = Self::Key, Element = Self>;
+}
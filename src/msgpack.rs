@@ -0,0 +1,1042 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::Hash;
+use std::io::{self, Write};
+
+#[cfg(feature = "custom-types")]
+use crate::ValueType;
+use crate::{
+    array::{Array, ArrayMut},
+    base::{TypedValue, ValueAsContainer, ValueAsMutContainer, ValueAsScalar},
+    generator::Node,
+    object::{Object, ObjectMut},
+    ValueBuilder,
+};
+
+const NIL: u8 = 0xc0;
+const FALSE: u8 = 0xc2;
+const TRUE: u8 = 0xc3;
+const FLOAT32: u8 = 0xca;
+const FLOAT64: u8 = 0xcb;
+const UINT8: u8 = 0xcc;
+const UINT16: u8 = 0xcd;
+const UINT32: u8 = 0xce;
+const UINT64: u8 = 0xcf;
+const INT8: u8 = 0xd0;
+const INT16: u8 = 0xd1;
+const INT32: u8 = 0xd2;
+const INT64: u8 = 0xd3;
+const STR8: u8 = 0xd9;
+const STR16: u8 = 0xda;
+const STR32: u8 = 0xdb;
+const ARRAY16: u8 = 0xdc;
+const ARRAY32: u8 = 0xdd;
+const MAP16: u8 = 0xde;
+const MAP32: u8 = 0xdf;
+#[cfg(feature = "custom-types")]
+const EXT8: u8 = 0xc7;
+#[cfg(feature = "custom-types")]
+const EXT16: u8 = 0xc8;
+#[cfg(feature = "custom-types")]
+const EXT32: u8 = 0xc9;
+#[cfg(feature = "custom-types")]
+const FIXEXT1: u8 = 0xd4;
+
+/// Maps custom value types to `MessagePack` extension types and back
+#[cfg(feature = "custom-types")]
+pub trait ExtensionMapper<V> {
+    /// Returns the extension type and payload for a value with the
+    /// custom type `name`, values that return `None` are written as
+    /// their regular representation
+    fn encode(&self, name: &'static str, value: &V) -> Option<(i8, Vec<u8>)>;
+    /// Builds a value from an extension type and its payload, returns
+    /// `None` for unknown extensions
+    fn decode(&self, ty: i8, data: &[u8]) -> Option<V>;
+}
+
+/// Encodes a value as `MessagePack` into a Writer using the most compact
+/// representation for each item
+///
+/// # Errors
+///
+/// Will return `Err` if an IO error is encountered or the value contains an
+/// integer that does not fit into 64 bit
+pub fn encode<V, W>(value: &V, writer: &mut W) -> io::Result<()>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+    W: Write,
+{
+    Encoder {
+        writer,
+        #[cfg(feature = "custom-types")]
+        extensions: None,
+        #[cfg(not(feature = "custom-types"))]
+        extensions: std::marker::PhantomData,
+    }
+    .value(value)
+}
+
+/// Encodes a value as `MessagePack` into a Writer, custom values are written
+/// as extension types using `extensions`
+///
+/// # Errors
+///
+/// Will return `Err` if an IO error is encountered or the value contains an
+/// integer that does not fit into 64 bit
+#[cfg(feature = "custom-types")]
+pub fn encode_with_extensions<V, W>(
+    value: &V,
+    writer: &mut W,
+    extensions: &dyn ExtensionMapper<V>,
+) -> io::Result<()>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+    W: Write,
+{
+    Encoder {
+        writer,
+        extensions: Some(extensions),
+    }
+    .value(value)
+}
+
+/// Encodes a value as `MessagePack`
+///
+/// # Errors
+///
+/// if the value contains an integer that does not fit into 64 bit
+pub fn to_vec<V>(value: &V) -> io::Result<Vec<u8>>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    let mut out = Vec::new();
+    encode(value, &mut out)?;
+    Ok(out)
+}
+
+struct Encoder<'w, 'e, W, V> {
+    writer: &'w mut W,
+    #[cfg(feature = "custom-types")]
+    extensions: Option<&'e dyn ExtensionMapper<V>>,
+    #[cfg(not(feature = "custom-types"))]
+    extensions: std::marker::PhantomData<&'e V>,
+}
+
+impl<W, V> Encoder<'_, '_, W, V>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+    W: Write,
+{
+    fn value(&mut self, value: &V) -> io::Result<()> {
+        #[cfg(feature = "custom-types")]
+        if let (Some(extensions), ValueType::Custom(name)) = (self.extensions, value.value_type()) {
+            if let Some((ty, data)) = extensions.encode(name, value) {
+                return self.write_extension(ty, &data);
+            }
+        }
+        match Node::of(value) {
+            Node::Null => self.writer.write_all(&[NIL]),
+            Node::Bool(b) => self.writer.write_all(&[if b { TRUE } else { FALSE }]),
+            Node::I64(n) => self.write_int(n),
+            Node::U64(n) => self.write_uint(n),
+            Node::I128(n) => match (u64::try_from(n), i64::try_from(n)) {
+                (Ok(n), _) => self.write_uint(n),
+                (_, Ok(n)) => self.write_int(n),
+                _ => Err(out_of_range()),
+            },
+            Node::U128(n) => u64::try_from(n)
+                .map_err(|_| out_of_range())
+                .and_then(|n| self.write_uint(n)),
+            Node::F64(n) => {
+                // use single precision if it is lossless
+                #[allow(clippy::cast_possible_truncation)]
+                let single = n as f32;
+                #[allow(clippy::float_cmp)]
+                if f64::from(single) == n || n.is_nan() {
+                    self.writer.write_all(&[FLOAT32])?;
+                    self.writer.write_all(&single.to_be_bytes())
+                } else {
+                    self.writer.write_all(&[FLOAT64])?;
+                    self.writer.write_all(&n.to_be_bytes())
+                }
+            }
+            Node::String(s) => self.write_str(s),
+            Node::Array(array) => {
+                self.write_len(0x90, ARRAY16, ARRAY32, array.len())?;
+                for item in array.iter() {
+                    self.value(item)?;
+                }
+                Ok(())
+            }
+            Node::Object(object) => {
+                self.write_len(0x80, MAP16, MAP32, object.len())?;
+                for (k, v) in object.iter() {
+                    self.write_str(k.borrow())?;
+                    self.value(v)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // n < 128
+    fn write_uint(&mut self, n: u64) -> io::Result<()> {
+        if n < 0x80 {
+            self.writer.write_all(&[n as u8])
+        } else if let Ok(n) = u8::try_from(n) {
+            self.writer.write_all(&[UINT8, n])
+        } else if let Ok(n) = u16::try_from(n) {
+            self.writer.write_all(&[UINT16])?;
+            self.writer.write_all(&n.to_be_bytes())
+        } else if let Ok(n) = u32::try_from(n) {
+            self.writer.write_all(&[UINT32])?;
+            self.writer.write_all(&n.to_be_bytes())
+        } else {
+            self.writer.write_all(&[UINT64])?;
+            self.writer.write_all(&n.to_be_bytes())
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // -32 <= n < 0
+    fn write_int(&mut self, n: i64) -> io::Result<()> {
+        if let Ok(n) = u64::try_from(n) {
+            self.write_uint(n)
+        } else if n >= -32 {
+            self.writer.write_all(&[n as u8])
+        } else if let Ok(n) = i8::try_from(n) {
+            self.writer.write_all(&[INT8])?;
+            self.writer.write_all(&n.to_be_bytes())
+        } else if let Ok(n) = i16::try_from(n) {
+            self.writer.write_all(&[INT16])?;
+            self.writer.write_all(&n.to_be_bytes())
+        } else if let Ok(n) = i32::try_from(n) {
+            self.writer.write_all(&[INT32])?;
+            self.writer.write_all(&n.to_be_bytes())
+        } else {
+            self.writer.write_all(&[INT64])?;
+            self.writer.write_all(&n.to_be_bytes())
+        }
+    }
+
+    /// Writes the marker for an array or map, `fix` is the marker for
+    /// the compact form with the length in the low 4 bits
+    #[allow(clippy::cast_possible_truncation)] // len < 16
+    fn write_len(&mut self, fix: u8, marker16: u8, marker32: u8, len: usize) -> io::Result<()> {
+        if len < 16 {
+            self.writer.write_all(&[fix | len as u8])
+        } else if let Ok(len) = u16::try_from(len) {
+            self.writer.write_all(&[marker16])?;
+            self.writer.write_all(&len.to_be_bytes())
+        } else {
+            let len = u32::try_from(len).map_err(|_| too_long())?;
+            self.writer.write_all(&[marker32])?;
+            self.writer.write_all(&len.to_be_bytes())
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)] // len < 32
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        let len = s.len();
+        if len < 32 {
+            self.writer.write_all(&[0xa0 | len as u8])?;
+        } else if let Ok(len) = u8::try_from(len) {
+            self.writer.write_all(&[STR8, len])?;
+        } else if let Ok(len) = u16::try_from(len) {
+            self.writer.write_all(&[STR16])?;
+            self.writer.write_all(&len.to_be_bytes())?;
+        } else {
+            let len = u32::try_from(len).map_err(|_| too_long())?;
+            self.writer.write_all(&[STR32])?;
+            self.writer.write_all(&len.to_be_bytes())?;
+        }
+        self.writer.write_all(s.as_bytes())
+    }
+
+    #[cfg(feature = "custom-types")]
+    fn write_extension(&mut self, ty: i8, data: &[u8]) -> io::Result<()> {
+        let len = data.len();
+        match len {
+            // fixext 1, 2, 4, 8 and 16
+            1 | 2 | 4 | 8 | 16 => {
+                #[allow(clippy::cast_possible_truncation)] // trailing_zeros < 5
+                let marker = FIXEXT1 + len.trailing_zeros() as u8;
+                self.writer.write_all(&[marker])?;
+            }
+            _ => {
+                if let Ok(len) = u8::try_from(len) {
+                    self.writer.write_all(&[EXT8, len])?;
+                } else if let Ok(len) = u16::try_from(len) {
+                    self.writer.write_all(&[EXT16])?;
+                    self.writer.write_all(&len.to_be_bytes())?;
+                } else {
+                    let len = u32::try_from(len).map_err(|_| too_long())?;
+                    self.writer.write_all(&[EXT32])?;
+                    self.writer.write_all(&len.to_be_bytes())?;
+                }
+            }
+        }
+        self.writer.write_all(&ty.to_be_bytes())?;
+        self.writer.write_all(data)
+    }
+}
+
+fn out_of_range() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "MessagePack integers are limited to 64 bit",
+    )
+}
+
+fn too_long() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "MessagePack items are limited to 2^32 - 1 elements",
+    )
+}
+
+/// Reader settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderConfig {
+    /// Maximum nesting of arrays and maps
+    pub max_depth: usize,
+}
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        Self { max_depth: 128 }
+    }
+}
+
+/// The kinds of errors the `MessagePack` reader can encounter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsgpackErrorKind {
+    /// The input ended in the middle of an item
+    Eof,
+    /// There is data left after the first item
+    TrailingData,
+    /// Arrays and maps are nested deeper than the configured limit
+    DepthLimitExceeded,
+    /// A string is not valid UTF-8
+    InvalidUtf8,
+    /// A map key is not a string
+    InvalidKey,
+    /// The marker byte is not used by `MessagePack`
+    InvalidMarker,
+    /// The item (like binary data or an unknown extension) has no
+    /// representation as a value
+    Unsupported,
+}
+
+impl fmt::Display for MsgpackErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eof => write!(f, "Unexpected end of input"),
+            Self::TrailingData => write!(f, "Trailing data after the item"),
+            Self::DepthLimitExceeded => write!(f, "Nesting depth limit exceeded"),
+            Self::InvalidUtf8 => write!(f, "String is not valid UTF-8"),
+            Self::InvalidKey => write!(f, "Map keys have to be strings"),
+            Self::InvalidMarker => write!(f, "Invalid marker byte"),
+            Self::Unsupported => write!(f, "Item can not be represented as a value"),
+        }
+    }
+}
+
+/// An error encountered while reading `MessagePack`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MsgpackError {
+    /// Byte offset of the item that caused the error
+    pub offset: usize,
+    /// What went wrong
+    pub kind: MsgpackErrorKind,
+}
+
+impl fmt::Display for MsgpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl std::error::Error for MsgpackError {}
+
+/// Reads a `MessagePack` item into a value using the default settings
+///
+/// # Errors
+///
+/// if the input is not a single valid `MessagePack` item that can be
+/// represented as a value
+pub fn decode<'input, V>(data: &'input [u8]) -> Result<V, MsgpackError>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<&'input str> + Hash + Eq,
+{
+    decode_with(data, ReaderConfig::default())
+}
+
+/// Reads a `MessagePack` item into a value
+///
+/// # Errors
+///
+/// if the input is not a single valid `MessagePack` item that can be
+/// represented as a value
+pub fn decode_with<'input, V>(data: &'input [u8], config: ReaderConfig) -> Result<V, MsgpackError>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<&'input str> + Hash + Eq,
+{
+    Decoder {
+        data,
+        idx: 0,
+        config,
+        #[cfg(feature = "custom-types")]
+        extensions: None,
+        #[cfg(not(feature = "custom-types"))]
+        extensions: std::marker::PhantomData,
+    }
+    .decode()
+}
+
+/// Reads a `MessagePack` item into a value, extension types are turned into
+/// values using `extensions`
+///
+/// # Errors
+///
+/// if the input is not a single valid `MessagePack` item that can be
+/// represented as a value
+#[cfg(feature = "custom-types")]
+pub fn decode_with_extensions<'input, V>(
+    data: &'input [u8],
+    config: ReaderConfig,
+    extensions: &dyn ExtensionMapper<V>,
+) -> Result<V, MsgpackError>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<&'input str> + Hash + Eq,
+{
+    Decoder {
+        data,
+        idx: 0,
+        config,
+        extensions: Some(extensions),
+    }
+    .decode()
+}
+
+fn error(offset: usize, kind: MsgpackErrorKind) -> MsgpackError {
+    MsgpackError { offset, kind }
+}
+
+struct Decoder<'input, 'e, V> {
+    data: &'input [u8],
+    idx: usize,
+    config: ReaderConfig,
+    #[cfg(feature = "custom-types")]
+    extensions: Option<&'e dyn ExtensionMapper<V>>,
+    #[cfg(not(feature = "custom-types"))]
+    extensions: std::marker::PhantomData<&'e V>,
+}
+
+impl<'input, V> Decoder<'input, '_, V>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<&'input str> + Hash + Eq,
+{
+    fn decode(mut self) -> Result<V, MsgpackError> {
+        let value = self.value(0)?;
+        if self.idx == self.data.len() {
+            Ok(value)
+        } else {
+            Err(error(self.idx, MsgpackErrorKind::TrailingData))
+        }
+    }
+
+    fn read(&mut self, n: usize) -> Result<&'input [u8], MsgpackError> {
+        // compare against what is left so a length from the input can't overflow
+        if n > self.data.len() - self.idx {
+            return Err(error(self.idx, MsgpackErrorKind::Eof));
+        }
+        let bytes = &self.data[self.idx..self.idx + n];
+        self.idx += n;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], MsgpackError> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.read(N)?);
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, MsgpackError> {
+        Ok(self.read(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<usize, MsgpackError> {
+        Ok(usize::from(u16::from_be_bytes(self.read_array()?)))
+    }
+
+    fn read_u32(&mut self) -> Result<usize, MsgpackError> {
+        let n = u32::from_be_bytes(self.read_array()?);
+        usize::try_from(n).map_err(|_| error(self.idx, MsgpackErrorKind::Eof))
+    }
+
+    /// Reads a string of `len` bytes, `start` is the offset of its marker
+    fn read_str(&mut self, start: usize, len: usize) -> Result<&'input str, MsgpackError> {
+        let bytes = self.read(len)?;
+        std::str::from_utf8(bytes).map_err(|_| error(start, MsgpackErrorKind::InvalidUtf8))
+    }
+
+    /// Reads a map key, which has to be a string
+    fn key(&mut self) -> Result<&'input str, MsgpackError> {
+        let start = self.idx;
+        let len = match self.read_u8()? {
+            marker @ 0xa0..=0xbf => usize::from(marker & 0x1f),
+            STR8 => usize::from(self.read_u8()?),
+            STR16 => self.read_u16()?,
+            STR32 => self.read_u32()?,
+            _ => return Err(error(start, MsgpackErrorKind::InvalidKey)),
+        };
+        self.read_str(start, len)
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<V, MsgpackError> {
+        // never trust the length for pre-allocation more than the input
+        let mut array = V::array_with_capacity(len.min(self.data.len() - self.idx));
+        for _ in 0..len {
+            let item = self.value(depth + 1)?;
+            if let Some(array) = array.as_array_mut() {
+                array.push(item);
+            }
+        }
+        Ok(array)
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<V, MsgpackError> {
+        let mut object = V::object_with_capacity(len.min(self.data.len() - self.idx));
+        for _ in 0..len {
+            let key = self.key()?;
+            let item = self.value(depth + 1)?;
+            if let Some(object) = object.as_object_mut() {
+                let _: Option<_> = object.insert(key, item);
+            }
+        }
+        Ok(object)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<V, MsgpackError> {
+        let start = self.idx;
+        let marker = self.read_u8()?;
+        if matches!(marker, 0x80..=0x9f | ARRAY16 | ARRAY32 | MAP16 | MAP32)
+            && depth >= self.config.max_depth
+        {
+            return Err(error(start, MsgpackErrorKind::DepthLimitExceeded));
+        }
+        match marker {
+            0x00..=0x7f => Ok(V::from(marker)),
+            0x80..=0x8f => self.map(usize::from(marker & 0x0f), depth),
+            0x90..=0x9f => self.array(usize::from(marker & 0x0f), depth),
+            0xa0..=0xbf => self
+                .read_str(start, usize::from(marker & 0x1f))
+                .map(V::from),
+            NIL => Ok(V::null()),
+            FALSE => Ok(V::from(false)),
+            TRUE => Ok(V::from(true)),
+            FLOAT32 => Ok(V::from(f32::from_be_bytes(self.read_array()?))),
+            FLOAT64 => Ok(V::from(f64::from_be_bytes(self.read_array()?))),
+            UINT8 => Ok(V::from(self.read_u8()?)),
+            UINT16 => Ok(V::from(u16::from_be_bytes(self.read_array()?))),
+            UINT32 => Ok(V::from(u32::from_be_bytes(self.read_array()?))),
+            UINT64 => Ok(V::from(u64::from_be_bytes(self.read_array()?))),
+            INT8 => Ok(V::from(i8::from_be_bytes(self.read_array()?))),
+            INT16 => Ok(V::from(i16::from_be_bytes(self.read_array()?))),
+            INT32 => Ok(V::from(i32::from_be_bytes(self.read_array()?))),
+            INT64 => Ok(V::from(i64::from_be_bytes(self.read_array()?))),
+            STR8 => {
+                let len = usize::from(self.read_u8()?);
+                self.read_str(start, len).map(V::from)
+            }
+            STR16 => {
+                let len = self.read_u16()?;
+                self.read_str(start, len).map(V::from)
+            }
+            STR32 => {
+                let len = self.read_u32()?;
+                self.read_str(start, len).map(V::from)
+            }
+            ARRAY16 => {
+                let len = self.read_u16()?;
+                self.array(len, depth)
+            }
+            ARRAY32 => {
+                let len = self.read_u32()?;
+                self.array(len, depth)
+            }
+            MAP16 => {
+                let len = self.read_u16()?;
+                self.map(len, depth)
+            }
+            MAP32 => {
+                let len = self.read_u32()?;
+                self.map(len, depth)
+            }
+            // ext 8, 16, 32 and fixext 1, 2, 4, 8, 16
+            0xc7..=0xc9 | 0xd4..=0xd8 => {
+                let len = match marker {
+                    0xc7 => usize::from(self.read_u8()?),
+                    0xc8 => self.read_u16()?,
+                    0xc9 => self.read_u32()?,
+                    _ => 1 << (marker - 0xd4),
+                };
+                let ty = i8::from_be_bytes(self.read_array()?);
+                let data = self.read(len)?;
+                self.extension(ty, data)
+                    .ok_or_else(|| error(start, MsgpackErrorKind::Unsupported))
+            }
+            // bin 8, 16, 32
+            0xc4..=0xc6 => Err(error(start, MsgpackErrorKind::Unsupported)),
+            0xe0..=0xff => Ok(V::from(i8::from_be_bytes([marker]))),
+            // 0xc1 is never used
+            _ => Err(error(start, MsgpackErrorKind::InvalidMarker)),
+        }
+    }
+
+    #[cfg(feature = "custom-types")]
+    fn extension(&self, ty: i8, data: &[u8]) -> Option<V> {
+        self.extensions?.decode(ty, data)
+    }
+
+    #[cfg(not(feature = "custom-types"))]
+    #[allow(clippy::unused_self)]
+    fn extension(&self, _ty: i8, _data: &[u8]) -> Option<V> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, decode_with, to_vec, MsgpackErrorKind, ReaderConfig};
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+
+    fn err(data: &[u8]) -> (usize, MsgpackErrorKind) {
+        let e = decode::<NativeValue>(data).expect_err("invalid input");
+        (e.offset, e.kind)
+    }
+
+    fn round_trip(value: &NativeValue) -> Vec<u8> {
+        let bytes = to_vec(value).expect("encodable");
+        assert_eq!(decode::<NativeValue>(&bytes).as_ref(), Ok(value));
+        bytes
+    }
+
+    #[test]
+    fn integers() {
+        for (n, expected) in [
+            (0_i64, &[0x00][..]),
+            (127, &[0x7f]),
+            (128, &[0xcc, 0x80]),
+            (256, &[0xcd, 0x01, 0x00]),
+            (65_536, &[0xce, 0x00, 0x01, 0x00, 0x00]),
+            (-1, &[0xff]),
+            (-32, &[0xe0]),
+            (-33, &[0xd0, 0xdf]),
+            (-129, &[0xd1, 0xff, 0x7f]),
+            (-32_769, &[0xd2, 0xff, 0xff, 0x7f, 0xff]),
+        ] {
+            assert_eq!(round_trip(&NativeValue::from(n)), expected, "{n}");
+        }
+        for n in [i64::MIN, i64::from(i32::MIN) - 1] {
+            assert_eq!(round_trip(&NativeValue::from(n)).len(), 9);
+        }
+        let bytes = round_trip(&NativeValue::from(u64::MAX));
+        assert_eq!(
+            bytes,
+            [0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(round_trip(&NativeValue::from(0.5)), [0xca, 0x3f, 0, 0, 0]);
+        assert_eq!(round_trip(&NativeValue::from(0.1))[0], 0xcb);
+        let bytes = to_vec(&NativeValue::from(f64::NAN)).expect("encodable");
+        let nan: NativeValue = decode(&bytes).expect("valid");
+        assert!(nan.as_f64().map_or(false, f64::is_nan));
+    }
+
+    #[test]
+    fn strings_and_containers() {
+        for (len, header) in [
+            (0, &[0xa0][..]),
+            (31, &[0xbf]),
+            (32, &[0xd9, 32]),
+            (256, &[0xda, 0x01, 0x00]),
+            (65_536, &[0xdb, 0x00, 0x01, 0x00, 0x00]),
+        ] {
+            let bytes = round_trip(&NativeValue::from("x".repeat(len)));
+            assert!(bytes.starts_with(header), "{len}");
+            assert_eq!(bytes.len(), header.len() + len);
+        }
+        for (len, header) in [(15, &[0x9f][..]), (16, &[0xdc, 0, 16])] {
+            let bytes = round_trip(&NativeValue::from(vec![true; len]));
+            assert!(bytes.starts_with(header), "{len}");
+        }
+        let map: HashMap<String, NativeValue> = (0..16_u64)
+            .map(|i| (i.to_string(), NativeValue::from(vec![i])))
+            .collect();
+        let bytes = round_trip(&NativeValue::from(map));
+        assert_eq!(bytes[..3], [0xde, 0, 16]);
+        round_trip(&NativeValue::from(vec![
+            NativeValue::null(),
+            NativeValue::from(false),
+            NativeValue::from("ünïcödé"),
+            NativeValue::from(HashMap::<String, NativeValue>::new()),
+        ]));
+    }
+
+    #[cfg(feature = "128bit")]
+    #[test]
+    fn out_of_range() {
+        use crate::StaticNode;
+        let value = NativeValue::from(StaticNode::U128(u128::from(u64::MAX) + 1));
+        let e = to_vec(&value).expect_err("too large");
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        let value = NativeValue::from(StaticNode::I128(-1));
+        assert_eq!(round_trip(&value), [0xff]);
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(err(&[]), (0, MsgpackErrorKind::Eof));
+        assert_eq!(err(&[0xc1]), (0, MsgpackErrorKind::InvalidMarker));
+        assert_eq!(err(&[0xa2, b'a']), (1, MsgpackErrorKind::Eof));
+        assert_eq!(err(&[0xcd, 0x01]), (1, MsgpackErrorKind::Eof));
+        assert_eq!(err(&[0xc0, 0xc0]), (1, MsgpackErrorKind::TrailingData));
+        assert_eq!(err(&[0x91, 0xa1, 0xff]), (1, MsgpackErrorKind::InvalidUtf8));
+        assert_eq!(err(&[0x81, 0x01, 0x01]), (1, MsgpackErrorKind::InvalidKey));
+        assert_eq!(err(&[0xc4, 0x00]), (0, MsgpackErrorKind::Unsupported));
+        assert_eq!(err(&[0xd4, 0x01, 0x00]), (0, MsgpackErrorKind::Unsupported));
+    }
+
+    #[test]
+    fn huge_lengths() {
+        // lengths are never trusted for allocations or offsets
+        assert_eq!(
+            err(&[0xdb, 0xff, 0xff, 0xff, 0xff]),
+            (5, MsgpackErrorKind::Eof)
+        );
+        assert_eq!(
+            err(&[0xdd, 0xff, 0xff, 0xff, 0xff, 0xc0]),
+            (6, MsgpackErrorKind::Eof)
+        );
+        assert_eq!(
+            err(&[0xdf, 0xff, 0xff, 0xff, 0xff]),
+            (5, MsgpackErrorKind::Eof)
+        );
+        assert_eq!(
+            err(&[0xc9, 0xff, 0xff, 0xff, 0xff, 0x01]),
+            (6, MsgpackErrorKind::Eof)
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        let data = vec![0x91; 1_000_000];
+        assert_eq!(err(&data), (128, MsgpackErrorKind::DepthLimitExceeded));
+        let config = ReaderConfig { max_depth: 2 };
+        assert!(decode_with::<NativeValue>(&[0x91, 0x81, 0xa1, b'k', 0xc0], config).is_ok());
+        let e =
+            decode_with::<NativeValue>(&[0x91, 0x91, 0x91, 0xc0], config).expect_err("too deep");
+        assert_eq!(
+            (e.offset, e.kind),
+            (2, MsgpackErrorKind::DepthLimitExceeded)
+        );
+    }
+
+    /// Value with a custom `bytes` type that is mapped to extensions
+    #[cfg(feature = "custom-types")]
+    mod ext {
+        use crate::base::{TypedValue, ValueAsContainer, ValueAsMutContainer, ValueAsScalar};
+        use crate::msgpack::ExtensionMapper;
+        use crate::{StaticNode, ValueBuilder, ValueType};
+        use std::borrow::Cow;
+        use std::collections::HashMap;
+
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub enum Ext {
+            #[default]
+            Null,
+            Static(StaticNode),
+            String(String),
+            Bytes(i8, Vec<u8>),
+            Array(Vec<Ext>),
+            Object(HashMap<String, Ext>),
+        }
+
+        macro_rules! from {
+            ($($t:ty),*) => {
+                $(
+                    impl From<$t> for Ext {
+                        fn from(v: $t) -> Self {
+                            Self::Static(StaticNode::from(v))
+                        }
+                    }
+                )*
+            };
+        }
+
+        from!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64, bool, ());
+
+        impl From<StaticNode> for Ext {
+            fn from(v: StaticNode) -> Self {
+                Self::Static(v)
+            }
+        }
+
+        impl From<String> for Ext {
+            fn from(v: String) -> Self {
+                Self::String(v)
+            }
+        }
+
+        impl From<&str> for Ext {
+            fn from(v: &str) -> Self {
+                Self::String(v.to_string())
+            }
+        }
+
+        impl From<Cow<'_, str>> for Ext {
+            fn from(v: Cow<'_, str>) -> Self {
+                Self::String(v.into_owned())
+            }
+        }
+
+        #[cfg(feature = "128bit")]
+        from!(i128, u128);
+
+        impl From<Vec<Ext>> for Ext {
+            fn from(v: Vec<Ext>) -> Self {
+                Self::Array(v)
+            }
+        }
+
+        impl From<HashMap<String, Ext>> for Ext {
+            fn from(v: HashMap<String, Ext>) -> Self {
+                Self::Object(v)
+            }
+        }
+
+        impl From<Option<Ext>> for Ext {
+            fn from(v: Option<Ext>) -> Self {
+                v.unwrap_or_default()
+            }
+        }
+
+        impl FromIterator<Ext> for Ext {
+            fn from_iter<I: IntoIterator<Item = Ext>>(iter: I) -> Self {
+                Self::Array(iter.into_iter().collect())
+            }
+        }
+
+        impl FromIterator<(String, Ext)> for Ext {
+            fn from_iter<I: IntoIterator<Item = (String, Ext)>>(iter: I) -> Self {
+                Self::Object(iter.into_iter().collect())
+            }
+        }
+
+        impl ValueBuilder<'_> for Ext {
+            fn array_with_capacity(capacity: usize) -> Self {
+                Self::Array(Vec::with_capacity(capacity))
+            }
+
+            fn object_with_capacity(capacity: usize) -> Self {
+                Self::Object(HashMap::with_capacity(capacity))
+            }
+
+            fn null() -> Self {
+                Self::Null
+            }
+        }
+
+        impl TypedValue for Ext {
+            fn value_type(&self) -> ValueType {
+                match self {
+                    Self::Null => ValueType::Null,
+                    Self::Static(s) => s.value_type(),
+                    Self::String(_) => ValueType::String,
+                    Self::Bytes(..) => ValueType::Custom("bytes"),
+                    Self::Array(_) => ValueType::Array,
+                    Self::Object(_) => ValueType::Object,
+                }
+            }
+        }
+
+        impl Ext {
+            fn as_static(&self) -> Option<&StaticNode> {
+                if let Self::Static(s) = self {
+                    Some(s)
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl ValueAsScalar for Ext {
+            fn as_null(&self) -> Option<()> {
+                matches!(self, Self::Null).then_some(())
+            }
+
+            fn as_bool(&self) -> Option<bool> {
+                self.as_static()?.as_bool()
+            }
+
+            fn as_i64(&self) -> Option<i64> {
+                self.as_static()?.as_i64()
+            }
+
+            fn as_u64(&self) -> Option<u64> {
+                self.as_static()?.as_u64()
+            }
+
+            fn as_f64(&self) -> Option<f64> {
+                self.as_static()?.as_f64()
+            }
+
+            fn as_str(&self) -> Option<&str> {
+                if let Self::String(s) = self {
+                    Some(s)
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl ValueAsContainer for Ext {
+            type Array = Vec<Self>;
+            type Object = HashMap<String, Self>;
+
+            fn as_array(&self) -> Option<&Vec<Self>> {
+                if let Self::Array(a) = self {
+                    Some(a)
+                } else {
+                    None
+                }
+            }
+
+            fn as_object(&self) -> Option<&HashMap<String, Self>> {
+                if let Self::Object(o) = self {
+                    Some(o)
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl ValueAsMutContainer for Ext {
+            type Array = Vec<Self>;
+            type Object = HashMap<String, Self>;
+
+            fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+                if let Self::Array(a) = self {
+                    Some(a)
+                } else {
+                    None
+                }
+            }
+
+            fn as_object_mut(&mut self) -> Option<&mut HashMap<String, Self>> {
+                if let Self::Object(o) = self {
+                    Some(o)
+                } else {
+                    None
+                }
+            }
+        }
+
+        /// Maps the extension types 1 and -1 to `Bytes`
+        pub struct Mapper;
+
+        impl ExtensionMapper<Ext> for Mapper {
+            fn encode(&self, name: &'static str, value: &Ext) -> Option<(i8, Vec<u8>)> {
+                match (name, value) {
+                    ("bytes", Ext::Bytes(ty, data)) => Some((*ty, data.clone())),
+                    _ => None,
+                }
+            }
+
+            fn decode(&self, ty: i8, data: &[u8]) -> Option<Ext> {
+                matches!(ty, 1 | -1).then(|| Ext::Bytes(ty, data.to_vec()))
+            }
+        }
+    }
+
+    #[cfg(feature = "custom-types")]
+    #[test]
+    fn extensions() {
+        use super::{decode_with_extensions, encode_with_extensions};
+        use ext::{Ext, Mapper};
+
+        let encode = |value: &Ext| {
+            let mut out = Vec::new();
+            encode_with_extensions(value, &mut out, &Mapper).expect("encodable");
+            out
+        };
+        let decode = |data: &[u8]| decode_with_extensions(data, ReaderConfig::default(), &Mapper);
+
+        let cases: [(usize, &[u8]); 8] = [
+            (1, &[0xd4]),
+            (2, &[0xd5]),
+            (4, &[0xd6]),
+            (8, &[0xd7]),
+            (16, &[0xd8]),
+            (3, &[0xc7, 3]),
+            (300, &[0xc8, 0x01, 0x2c]),
+            (70_000, &[0xc9, 0x00, 0x01, 0x11, 0x70]),
+        ];
+        for (len, header) in cases {
+            #[allow(clippy::cast_possible_truncation)] // we want the low byte
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let value = Ext::Bytes(-1, data.clone());
+            let bytes = encode(&value);
+            assert_eq!(&bytes[..header.len()], header, "{len}");
+            assert_eq!(bytes[header.len()], 0xff, "{len}");
+            assert_eq!(&bytes[header.len() + 1..], data.as_slice());
+            assert_eq!(decode(&bytes), Ok(value));
+        }
+
+        // nested in containers next to regular values
+        let mut object = HashMap::new();
+        object.insert("b".to_string(), Ext::Bytes(1, vec![7; 5]));
+        object.insert("s".to_string(), Ext::from("s"));
+        let value = Ext::Array(vec![Ext::Object(object), Ext::from(1_u8), Ext::Null]);
+        assert_eq!(decode(&encode(&value)), Ok(value));
+
+        // without a mapper and with a mapper that doesn't know the type
+        let known = [0xd4, 0x01, 0x2a];
+        assert_eq!(decode(&known), Ok(Ext::Bytes(1, vec![42])));
+        assert_eq!(err(&known), (0, MsgpackErrorKind::Unsupported));
+        let unknown = [0x91, 0xc7, 0x01, 0x05, 0x2a];
+        let e = decode(&unknown).expect_err("unknown extension");
+        assert_eq!((e.offset, e.kind), (1, MsgpackErrorKind::Unsupported));
+        assert_eq!(err(&unknown), (1, MsgpackErrorKind::Unsupported));
+    }
+}
@@ -0,0 +1,661 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::io::{self, Write};
+
+use crate::{
+    base::{
+        TypedCustomValue, TypedValue, ValueAsContainer, ValueAsMutContainer, ValueAsScalar,
+        ValueIntoContainer, ValueIntoString, Writable,
+    },
+    generator::{
+        BaseGenerator, DumpGenerator, PrettyGenerator, PrettyWriterGenerator, WriterGenerator,
+    },
+    StaticNode, ValueBuilder, ValueType,
+};
+
+/// Scalars are represented by their `StaticNode`, they have no containers
+/// so their arrays and objects are never returned
+macro_rules! static_value {
+    ($($t:ty),*) => {
+        $(
+            impl TypedValue for $t {
+                #[inline]
+                fn value_type(&self) -> ValueType {
+                    StaticNode::from(*self).value_type()
+                }
+            }
+
+            impl ValueAsScalar for $t {
+                #[inline]
+                fn as_null(&self) -> Option<()> {
+                    StaticNode::from(*self).as_null()
+                }
+
+                #[inline]
+                fn as_bool(&self) -> Option<bool> {
+                    StaticNode::from(*self).as_bool()
+                }
+
+                #[inline]
+                fn as_i64(&self) -> Option<i64> {
+                    StaticNode::from(*self).as_i64()
+                }
+
+                #[inline]
+                fn as_i128(&self) -> Option<i128> {
+                    StaticNode::from(*self).as_i128()
+                }
+
+                #[inline]
+                fn as_u64(&self) -> Option<u64> {
+                    StaticNode::from(*self).as_u64()
+                }
+
+                #[inline]
+                fn as_u128(&self) -> Option<u128> {
+                    StaticNode::from(*self).as_u128()
+                }
+
+                #[inline]
+                fn as_f64(&self) -> Option<f64> {
+                    StaticNode::from(*self).as_f64()
+                }
+
+                #[inline]
+                fn cast_f64(&self) -> Option<f64> {
+                    StaticNode::from(*self).cast_f64()
+                }
+
+                #[inline]
+                fn as_str(&self) -> Option<&str> {
+                    None
+                }
+            }
+
+            impl ValueAsContainer for $t {
+                type Array = Vec<Self>;
+                type Object = HashMap<String, Self>;
+
+                #[inline]
+                fn as_array(&self) -> Option<&Self::Array> {
+                    None
+                }
+
+                #[inline]
+                fn as_object(&self) -> Option<&Self::Object> {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+static_value!(i64, u64, f64, bool, ());
+
+/// Strings only have a string representation
+macro_rules! string_value {
+    ($($t:ty),*) => {
+        $(
+            impl TypedValue for $t {
+                #[inline]
+                fn value_type(&self) -> ValueType {
+                    ValueType::String
+                }
+            }
+
+            impl ValueAsScalar for $t {
+                #[inline]
+                fn as_null(&self) -> Option<()> {
+                    None
+                }
+
+                #[inline]
+                fn as_bool(&self) -> Option<bool> {
+                    None
+                }
+
+                #[inline]
+                fn as_i64(&self) -> Option<i64> {
+                    None
+                }
+
+                #[inline]
+                fn as_u64(&self) -> Option<u64> {
+                    None
+                }
+
+                #[inline]
+                fn as_f64(&self) -> Option<f64> {
+                    None
+                }
+
+                #[inline]
+                fn as_str(&self) -> Option<&str> {
+                    Some(self)
+                }
+            }
+
+            impl ValueAsContainer for $t {
+                type Array = Vec<String>;
+                type Object = HashMap<String, String>;
+
+                #[inline]
+                fn as_array(&self) -> Option<&Self::Array> {
+                    None
+                }
+
+                #[inline]
+                fn as_object(&self) -> Option<&Self::Object> {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+// `&str` is covered by the impls for references
+string_value!(String, str);
+
+/// Containers have no scalar representation
+macro_rules! no_scalar {
+    () => {
+        #[inline]
+        fn as_null(&self) -> Option<()> {
+            None
+        }
+
+        #[inline]
+        fn as_bool(&self) -> Option<bool> {
+            None
+        }
+
+        #[inline]
+        fn as_i64(&self) -> Option<i64> {
+            None
+        }
+
+        #[inline]
+        fn as_u64(&self) -> Option<u64> {
+            None
+        }
+
+        #[inline]
+        fn as_f64(&self) -> Option<f64> {
+            None
+        }
+
+        #[inline]
+        fn as_str(&self) -> Option<&str> {
+            None
+        }
+    };
+}
+
+/// With the prelude in scope the derived object accessors apply to vectors
+/// too, so `vec.get(i)` has to be written as `ArrayTrait::get(&vec, i)` or
+/// `<[T]>::get(&vec, i)`
+impl<T> TypedValue for Vec<T> {
+    #[inline]
+    fn value_type(&self) -> ValueType {
+        ValueType::Array
+    }
+}
+
+impl<T> ValueAsScalar for Vec<T> {
+    no_scalar!();
+}
+
+impl<T> ValueAsContainer for Vec<T> {
+    type Array = Self;
+    type Object = HashMap<String, T>;
+
+    #[inline]
+    fn as_array(&self) -> Option<&Self::Array> {
+        Some(self)
+    }
+
+    #[inline]
+    fn as_object(&self) -> Option<&Self::Object> {
+        None
+    }
+}
+
+impl<T, S> TypedValue for HashMap<String, T, S> {
+    #[inline]
+    fn value_type(&self) -> ValueType {
+        ValueType::Object
+    }
+}
+
+impl<T, S> ValueAsScalar for HashMap<String, T, S> {
+    no_scalar!();
+}
+
+impl<T, S: BuildHasher> ValueAsContainer for HashMap<String, T, S> {
+    type Array = Vec<T>;
+    type Object = Self;
+
+    #[inline]
+    fn as_array(&self) -> Option<&Self::Array> {
+        None
+    }
+
+    #[inline]
+    fn as_object(&self) -> Option<&Self::Object> {
+        Some(self)
+    }
+}
+
+/// An owned recursive document made of plain rust data
+///
+/// Strings, vectors and maps implement the base value traits on their own;
+/// this enum is for documents that mix them, as a `Value` needs arrays and
+/// objects whose elements are the value type itself.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use value_trait::{generator::encoded_len, NativeValue};
+///
+/// let v = NativeValue::from(HashMap::from([("a".to_string(), vec![1, 2])]));
+/// assert_eq!(encoded_len(&v), r#"{"a":[1,2]}"#.len());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum NativeValue {
+    /// Null, bools and numbers
+    Static(StaticNode),
+    /// A string
+    String(String),
+    /// An array
+    Array(Vec<NativeValue>),
+    /// An object
+    Object(HashMap<String, NativeValue>),
+}
+
+impl Default for NativeValue {
+    #[inline]
+    fn default() -> Self {
+        Self::Static(StaticNode::Null)
+    }
+}
+
+impl From<StaticNode> for NativeValue {
+    #[inline]
+    fn from(s: StaticNode) -> Self {
+        Self::Static(s)
+    }
+}
+
+impl From<String> for NativeValue {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<&str> for NativeValue {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self::String(s.to_owned())
+    }
+}
+
+impl From<Cow<'_, str>> for NativeValue {
+    #[inline]
+    fn from(s: Cow<'_, str>) -> Self {
+        Self::String(s.into_owned())
+    }
+}
+
+impl<T> From<Vec<T>> for NativeValue
+where
+    T: Into<NativeValue>,
+{
+    #[inline]
+    fn from(v: Vec<T>) -> Self {
+        Self::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T, S> From<HashMap<String, T, S>> for NativeValue
+where
+    T: Into<NativeValue>,
+{
+    #[inline]
+    fn from(m: HashMap<String, T, S>) -> Self {
+        Self::Object(m.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+impl<T> From<Option<T>> for NativeValue
+where
+    T: Into<NativeValue>,
+{
+    #[inline]
+    fn from(v: Option<T>) -> Self {
+        v.map_or_else(Self::default, Into::into)
+    }
+}
+
+macro_rules! from_static {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for NativeValue {
+                #[inline]
+                fn from(v: $t) -> Self {
+                    Self::Static(StaticNode::from(v))
+                }
+            }
+        )*
+    };
+}
+
+from_static!(bool, (), i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+#[cfg(feature = "128bit")]
+from_static!(i128, u128);
+
+impl FromIterator<NativeValue> for NativeValue {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = NativeValue>>(iter: I) -> Self {
+        Self::Array(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<(String, NativeValue)> for NativeValue {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (String, NativeValue)>>(iter: I) -> Self {
+        Self::Object(iter.into_iter().collect())
+    }
+}
+
+impl ValueBuilder<'_> for NativeValue {
+    #[inline]
+    fn array_with_capacity(capacity: usize) -> Self {
+        Self::Array(Vec::with_capacity(capacity))
+    }
+
+    #[inline]
+    fn object_with_capacity(capacity: usize) -> Self {
+        Self::Object(HashMap::with_capacity(capacity))
+    }
+
+    #[inline]
+    fn null() -> Self {
+        Self::default()
+    }
+}
+
+impl NativeValue {
+    fn as_static(&self) -> Option<&StaticNode> {
+        if let Self::Static(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl TypedValue for NativeValue {
+    #[inline]
+    fn value_type(&self) -> ValueType {
+        match self {
+            Self::Static(s) => s.value_type(),
+            Self::String(_) => ValueType::String,
+            Self::Array(_) => ValueType::Array,
+            Self::Object(_) => ValueType::Object,
+        }
+    }
+}
+
+impl TypedCustomValue for NativeValue {}
+
+impl ValueAsScalar for NativeValue {
+    #[inline]
+    fn as_null(&self) -> Option<()> {
+        self.as_static()?.as_null()
+    }
+
+    #[inline]
+    fn as_bool(&self) -> Option<bool> {
+        self.as_static()?.as_bool()
+    }
+
+    #[inline]
+    fn as_i128(&self) -> Option<i128> {
+        self.as_static()?.as_i128()
+    }
+
+    #[inline]
+    fn as_i64(&self) -> Option<i64> {
+        self.as_static()?.as_i64()
+    }
+
+    #[inline]
+    fn as_u128(&self) -> Option<u128> {
+        self.as_static()?.as_u128()
+    }
+
+    #[inline]
+    fn as_u64(&self) -> Option<u64> {
+        self.as_static()?.as_u64()
+    }
+
+    #[inline]
+    fn as_f64(&self) -> Option<f64> {
+        self.as_static()?.as_f64()
+    }
+
+    #[inline]
+    fn cast_f64(&self) -> Option<f64> {
+        self.as_static()?.cast_f64()
+    }
+
+    #[inline]
+    fn as_str(&self) -> Option<&str> {
+        if let Self::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl ValueAsContainer for NativeValue {
+    type Array = Vec<Self>;
+    type Object = HashMap<String, Self>;
+
+    #[inline]
+    fn as_array(&self) -> Option<&Vec<Self>> {
+        if let Self::Array(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn as_object(&self) -> Option<&HashMap<String, Self>> {
+        if let Self::Object(o) = self {
+            Some(o)
+        } else {
+            None
+        }
+    }
+}
+
+impl ValueAsMutContainer for NativeValue {
+    type Array = Vec<Self>;
+    type Object = HashMap<String, Self>;
+
+    #[inline]
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        if let Self::Array(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn as_object_mut(&mut self) -> Option<&mut HashMap<String, Self>> {
+        if let Self::Object(o) = self {
+            Some(o)
+        } else {
+            None
+        }
+    }
+}
+
+impl ValueIntoString for NativeValue {
+    type String = String;
+
+    #[inline]
+    fn into_string(self) -> Option<String> {
+        if let Self::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl ValueIntoContainer for NativeValue {
+    type Array = Vec<Self>;
+    type Object = HashMap<String, Self>;
+
+    #[inline]
+    fn into_array(self) -> Option<Vec<Self>> {
+        if let Self::Array(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn into_object(self) -> Option<HashMap<String, Self>> {
+        if let Self::Object(o) = self {
+            Some(o)
+        } else {
+            None
+        }
+    }
+}
+
+impl Writable for NativeValue {
+    fn encode(&self) -> String {
+        let mut g = DumpGenerator::new();
+        // writing to a String can not fail
+        let _: io::Result<()> = g.write_json(self);
+        g.consume()
+    }
+
+    fn encode_pp(&self) -> String {
+        let mut g = PrettyGenerator::new(2);
+        let _: io::Result<()> = g.write_json(self);
+        g.consume()
+    }
+
+    fn write<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        WriterGenerator::new(w).write_json(self)
+    }
+
+    fn write_pp<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        PrettyWriterGenerator::new(w, 2).write_json(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NativeValue;
+    use crate::generator::{encoded_len, encoded_len_pretty};
+    use crate::prelude::*;
+    use std::collections::HashMap;
+
+    fn doc() -> NativeValue {
+        let mut inner = HashMap::new();
+        inner.insert("b".to_string(), NativeValue::from(vec![1_i64, -2, 3]));
+        inner.insert("c\n\"".to_string(), NativeValue::from("x\u{1f}y"));
+        NativeValue::from(vec![
+            NativeValue::from(inner),
+            NativeValue::from(Some(1.5)),
+            NativeValue::from(None::<bool>),
+            NativeValue::from(Vec::<u8>::new()),
+            NativeValue::from(HashMap::<String, u8>::new()),
+            NativeValue::from(true),
+        ])
+    }
+
+    #[test]
+    fn writers() {
+        let v = doc();
+        assert_eq!(encoded_len(&v), v.encode().len());
+        assert_eq!(encoded_len_pretty(&v, 2), v.encode_pp().len());
+        let mut out = Vec::new();
+        v.write(&mut out).expect("write to vec");
+        assert_eq!(out, v.encode().into_bytes());
+        out.clear();
+        v.write_pp(&mut out).expect("write to vec");
+        assert_eq!(out, v.encode_pp().into_bytes());
+    }
+
+    #[test]
+    fn access() {
+        let mut v = doc();
+        assert_eq!(v.get_idx(1).and_then(ValueAsScalar::as_f64), Some(1.5));
+        assert_eq!(v.get_idx(2).map(TypedScalarValue::is_null), Some(true));
+        let inner = v.get_idx_mut(0).expect("object");
+        assert_eq!(
+            inner.get("b").and_then(|b| b.get_idx(1)?.as_i64()),
+            Some(-2)
+        );
+        assert_eq!(
+            inner.remove("b"),
+            Ok(Some(NativeValue::from(vec![1, -2, 3])))
+        );
+        assert_eq!(v.into_array().map(|a| a.len()), Some(6));
+    }
+
+    #[test]
+    fn primitives() {
+        assert_eq!(7_i64.as_i64(), Some(7));
+        assert_eq!(true.value_type(), ValueType::Bool);
+        assert!(().is_null());
+        assert_eq!(encoded_len(&2.5_f64), 3);
+    }
+
+    #[test]
+    fn std_types() {
+        let s = String::from("snow");
+        assert_eq!(s.value_type(), ValueType::String);
+        assert_eq!(ValueAsScalar::as_str(&s), Some("snow"));
+        assert!(s.as_array().is_none());
+        let r: &str = "ice";
+        assert_eq!(ValueAsScalar::as_str(&r), Some("ice"));
+        assert_eq!(ValueAsScalar::as_str("ice"), Some("ice"));
+        assert_eq!(r.value_type(), ValueType::String);
+        assert!(r.as_i64().is_none());
+
+        let v = vec![1_i64, 2, 3];
+        assert_eq!(v.value_type(), ValueType::Array);
+        assert!(v.is_array());
+        assert_eq!(v.as_array(), Some(&v));
+        assert!(v.as_object().is_none() && v.as_null().is_none());
+        // the derived accessors shadow the slice methods of the same name
+        assert_eq!(ArrayTrait::get(&v, 0), Some(&1));
+        assert_eq!(<[i64]>::get(&v, 1), Some(&2));
+        assert_eq!(v.get_idx(2), Some(&3));
+
+        let m = HashMap::from([("a".to_string(), vec![true])]);
+        assert_eq!(m.value_type(), ValueType::Object);
+        assert_eq!(m.as_object(), Some(&m));
+        assert!(m.as_array().is_none() && m.as_str().is_none());
+        assert_eq!(m.get("a").and_then(|a| a.get_idx(0)), Some(&true));
+    }
+}
@@ -1,8 +1,8 @@
 #[cfg(feature = "halfbrown")]
 use halfbrown::HashMap as Halfbrown;
-#[cfg(feature = "hashbrown")]
+#[cfg(any(feature = "hashbrown", feature = "arena"))]
 use hashbrown::HashMap as Hashbrown;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 use std::{borrow::Borrow, hash::BuildHasher};
 
@@ -12,6 +12,18 @@ pub trait Object {
     type Key: ?Sized;
     /// The values in the object
     type Element;
+    /// Iterator over the key value pairs of the object
+    type Iter<'i>: Iterator<Item = (&'i Self::Key, &'i Self::Element)>
+    where
+        Self: 'i;
+    /// Iterator over the keys of the object
+    type Keys<'i>: Iterator<Item = &'i Self::Key>
+    where
+        Self: 'i;
+    /// Iterator over the values of the object
+    type Values<'i>: Iterator<Item = &'i Self::Element>
+    where
+        Self: 'i;
 
     /// Gets a ref to a value based on a key, returns `None` if the
     /// current Value isn't an Object or doesn't contain the key
@@ -24,15 +36,15 @@ pub trait Object {
 
     /// Iterates over the key value paris
     #[must_use]
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&Self::Key, &Self::Element)> + 'i>;
+    fn iter(&self) -> Self::Iter<'_>;
 
     /// Iterates over the keys
     #[must_use]
-    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Key> + 'i>;
+    fn keys(&self) -> Self::Keys<'_>;
 
     /// Iterates over the values
     #[must_use]
-    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Element> + 'i>;
+    fn values(&self) -> Self::Values<'_>;
 
     /// Number of key/value pairs
     #[must_use]
@@ -51,6 +63,23 @@ pub trait ObjectMut {
     type Key: ?Sized;
     /// The values in the object
     type Element;
+    /// Iterator over the key value pairs of the object with mutable values
+    type IterMut<'i>: Iterator<Item = (&'i Self::Key, &'i mut Self::Element)>
+    where
+        Self: 'i;
+    /// Iterator over mutable references to the values of the object
+    type ValuesMut<'i>: Iterator<Item = &'i mut Self::Element>
+    where
+        Self: 'i;
+    /// Iterator over the key value pairs removed by `drain`
+    type Drain<'i>: Iterator<Item = (Self::Key, Self::Element)>
+    where
+        Self: 'i,
+        Self::Key: Sized;
+    /// The vacant entry of the backing map returned by `entry`
+    type VacantEntry<'e>: VacantObjectEntry<'e, Element = Self::Element>
+    where
+        Self: 'e;
 
     /// Gets the value of a key as a mutable reference.
     #[must_use]
@@ -72,6 +101,162 @@ pub trait ObjectMut {
     where
         Self::Key: Borrow<Q>,
         Q: ?Sized + Hash + Eq + Ord;
+
+    /// Gets the entry for a key for in-place manipulation with a single
+    /// lookup
+    #[must_use]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        Self::Key: From<K> + Hash + Eq;
+
+    /// Iterates over the key value pairs with mutable values
+    #[must_use]
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
+
+    /// Iterates over mutable values
+    #[must_use]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_>;
+
+    /// Retains only the key value pairs for which `f` returns `true`
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool;
+
+    /// Removes all key value pairs, returning them as an iterator
+    #[must_use]
+    fn drain(&mut self) -> Self::Drain<'_>
+    where
+        Self::Key: Sized;
+
+    /// Removes all key value pairs
+    fn clear(&mut self);
+}
+
+/// Index based access for objects that keep their keys in order
+pub trait OrderedObject: Object {
+    /// Gets the key value pair at position `i`
+    #[must_use]
+    fn get_index(&self, i: usize) -> Option<(&Self::Key, &Self::Element)>;
+
+    /// Gets the first key value pair
+    #[must_use]
+    fn first(&self) -> Option<(&Self::Key, &Self::Element)> {
+        self.get_index(0)
+    }
+
+    /// Gets the last key value pair
+    #[must_use]
+    fn last(&self) -> Option<(&Self::Key, &Self::Element)> {
+        self.len().checked_sub(1).and_then(|i| self.get_index(i))
+    }
+
+    /// Removes a value from the object, shifting all following keys one
+    /// position forward to keep their order
+    #[must_use]
+    fn shift_remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord;
+
+    /// Inserts a value at position `i`, an existing key is moved to that
+    /// position and its old value is returned
+    ///
+    /// # Panics
+    ///
+    /// if `i` is out of bounds
+    fn insert_at<K, V>(&mut self, i: usize, k: K, v: V) -> Option<Self::Element>
+    where
+        Self::Key: From<K> + Hash + Eq,
+        V: Into<Self::Element>;
+
+    /// Moves the key value pair at position `from` to position `to`,
+    /// shifting the pairs in between
+    ///
+    /// # Panics
+    ///
+    /// if `from` or `to` are out of bounds
+    fn move_index(&mut self, from: usize, to: usize);
+
+    /// Sorts the key value pairs by their keys
+    fn sort_keys(&mut self)
+    where
+        Self::Key: Ord;
+}
+
+/// A vacant entry of an object, it inserts a value for the key it was
+/// created with
+pub trait VacantObjectEntry<'a> {
+    /// The values in the object
+    type Element: 'a;
+
+    /// Inserts the value and returns a mutable reference to it
+    fn insert(self, v: Self::Element) -> &'a mut Self::Element;
+}
+
+/// A view into a single entry of an object, obtained from
+/// `ObjectMut::entry`
+pub enum ObjectEntry<'a, E>
+where
+    E: VacantObjectEntry<'a>,
+{
+    /// The key is present, holds its value
+    Occupied(&'a mut E::Element),
+    /// The key is missing, holds the vacant entry of the backing map
+    Vacant(E),
+}
+
+impl<'a, E> ObjectEntry<'a, E>
+where
+    E: VacantObjectEntry<'a>,
+{
+    /// Inserts `default` if the entry is vacant and returns a mutable
+    /// reference to the value
+    #[inline]
+    pub fn or_insert(self, default: E::Element) -> &'a mut E::Element {
+        match self {
+            Self::Occupied(v) => v,
+            Self::Vacant(e) => e.insert(default),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant and returns
+    /// a mutable reference to the value
+    #[inline]
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut E::Element
+    where
+        F: FnOnce() -> E::Element,
+    {
+        match self {
+            Self::Occupied(v) => v,
+            Self::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    /// Inserts the default value if the entry is vacant and returns a
+    /// mutable reference to the value
+    #[inline]
+    pub fn or_default(self) -> &'a mut E::Element
+    where
+        E::Element: Default,
+    {
+        self.or_insert_with(E::Element::default)
+    }
+
+    /// Calls `f` on the value if the entry is occupied
+    #[inline]
+    #[must_use]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut E::Element),
+    {
+        match self {
+            Self::Occupied(v) => {
+                f(v);
+                Self::Occupied(v)
+            }
+            vacant @ Self::Vacant(_) => vacant,
+        }
+    }
 }
 
 #[cfg(feature = "halfbrown")]
@@ -82,6 +267,18 @@ where
 {
     type Key = MapK;
     type Element = MapE;
+    type Iter<'i>
+        = halfbrown::Iter<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Keys<'i>
+        = halfbrown::Keys<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Values<'i>
+        = halfbrown::Values<'i, MapK, MapE>
+    where
+        Self: 'i;
 
     #[inline]
     fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
@@ -93,18 +290,18 @@ where
     }
 
     #[inline]
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&Self::Key, &Self::Element)> + 'i> {
-        Box::new(Halfbrown::iter(self))
+    fn iter(&self) -> Self::Iter<'_> {
+        Halfbrown::iter(self)
     }
 
     #[inline]
-    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Key> + 'i> {
-        Box::new(Halfbrown::keys(self))
+    fn keys(&self) -> Self::Keys<'_> {
+        Halfbrown::keys(self)
     }
 
     #[inline]
-    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Element> + 'i> {
-        Box::new(Halfbrown::values(self))
+    fn values(&self) -> Self::Values<'_> {
+        Halfbrown::values(self)
     }
 
     #[inline]
@@ -113,6 +310,7 @@ where
     }
 }
 
+#[cfg(feature = "halfbrown")]
 impl<MapK, MapE, S> ObjectMut for Halfbrown<MapK, MapE, S>
 where
     MapK: Hash + Eq,
@@ -120,6 +318,22 @@ where
 {
     type Key = MapK;
     type Element = MapE;
+    type IterMut<'i>
+        = halfbrown::IterMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = halfbrown::ValuesMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Drain<'i>
+        = halfbrown::Drain<'i, MapK, MapE, 32>
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = halfbrown::VacantEntry<'e, MapK, MapE, 32, S>
+    where
+        Self: 'e;
 
     #[inline]
     fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
@@ -148,6 +362,140 @@ where
     {
         Halfbrown::remove(self, k)
     }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        match Halfbrown::entry(self, k.into()) {
+            halfbrown::Entry::Occupied(e) => ObjectEntry::Occupied(e.into_mut()),
+            halfbrown::Entry::Vacant(e) => ObjectEntry::Vacant(e),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        Halfbrown::iter_mut(self)
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        Halfbrown::values_mut(self)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        Halfbrown::retain(self, f);
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        Halfbrown::drain(self)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        Halfbrown::clear(self);
+    }
+}
+
+#[cfg(feature = "halfbrown")]
+impl<'a, MapK, MapE, S> VacantObjectEntry<'a> for halfbrown::VacantEntry<'a, MapK, MapE, 32, S>
+where
+    MapK: Hash,
+    S: BuildHasher,
+{
+    type Element = MapE;
+
+    #[inline]
+    fn insert(self, v: MapE) -> &'a mut MapE {
+        halfbrown::VacantEntry::insert(self, v)
+    }
+}
+
+/// Puts the pairs back into an emptied map in their order
+#[cfg(all(feature = "halfbrown", feature = "preserve_order"))]
+fn refill<MapK, MapE, S>(map: &mut Halfbrown<MapK, MapE, S>, pairs: Vec<(MapK, MapE)>)
+where
+    MapK: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    for (k, v) in pairs {
+        map.insert(k, v);
+    }
+}
+
+/// With `preserve_order` halfbrown keeps its keys in insertion order.
+///
+/// halfbrown has no positional API so every operation is O(n): `get_index`
+/// walks the pairs up to `i`, while `shift_remove`, `insert_at`,
+/// `move_index` and `sort_keys` drain the whole map and insert all pairs
+/// again, which rehashes every key. Use an `indexmap::IndexMap` for large
+/// objects that are reordered often.
+#[cfg(all(feature = "halfbrown", feature = "preserve_order"))]
+impl<MapK, MapE, S> OrderedObject for Halfbrown<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn get_index(&self, i: usize) -> Option<(&Self::Key, &Self::Element)> {
+        Halfbrown::iter(self).nth(i)
+    }
+
+    #[inline]
+    fn shift_remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        let i = Halfbrown::keys(self).position(|key| key.borrow() == k)?;
+        let mut pairs: Vec<(MapK, MapE)> = Halfbrown::drain(self).collect();
+        let (_, v) = pairs.remove(i);
+        refill(self, pairs);
+        Some(v)
+    }
+
+    #[inline]
+    fn insert_at<K, V>(&mut self, i: usize, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        let k = k.into();
+        let mut pairs: Vec<(MapK, MapE)> = Halfbrown::drain(self).collect();
+        let old = pairs
+            .iter()
+            .position(|(key, _)| *key == k)
+            .map(|idx| pairs.remove(idx).1);
+        pairs.insert(i, (k, v.into()));
+        refill(self, pairs);
+        old
+    }
+
+    #[inline]
+    fn move_index(&mut self, from: usize, to: usize) {
+        let mut pairs: Vec<(MapK, MapE)> = Halfbrown::drain(self).collect();
+        let pair = pairs.remove(from);
+        pairs.insert(to, pair);
+        refill(self, pairs);
+    }
+
+    #[inline]
+    fn sort_keys(&mut self)
+    where
+        Self::Key: Ord,
+    {
+        let mut pairs: Vec<(MapK, MapE)> = Halfbrown::drain(self).collect();
+        pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        refill(self, pairs);
+    }
 }
 
 impl<MapK, MapE, S: BuildHasher> Object for HashMap<MapK, MapE, S>
@@ -156,6 +504,18 @@ where
 {
     type Key = MapK;
     type Element = MapE;
+    type Iter<'i>
+        = std::collections::hash_map::Iter<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Keys<'i>
+        = std::collections::hash_map::Keys<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Values<'i>
+        = std::collections::hash_map::Values<'i, MapK, MapE>
+    where
+        Self: 'i;
 
     #[inline]
     fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
@@ -167,18 +527,18 @@ where
     }
 
     #[inline]
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&Self::Key, &Self::Element)> + 'i> {
-        Box::new(HashMap::iter(self))
+    fn iter(&self) -> Self::Iter<'_> {
+        HashMap::iter(self)
     }
 
     #[inline]
-    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Key> + 'i> {
-        Box::new(HashMap::keys(self))
+    fn keys(&self) -> Self::Keys<'_> {
+        HashMap::keys(self)
     }
 
     #[inline]
-    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Element> + 'i> {
-        Box::new(HashMap::values(self))
+    fn values(&self) -> Self::Values<'_> {
+        HashMap::values(self)
     }
 
     #[inline]
@@ -193,6 +553,22 @@ where
 {
     type Key = MapK;
     type Element = MapE;
+    type IterMut<'i>
+        = std::collections::hash_map::IterMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = std::collections::hash_map::ValuesMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Drain<'i>
+        = std::collections::hash_map::Drain<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = std::collections::hash_map::VacantEntry<'e, MapK, MapE>
+    where
+        Self: 'e;
 
     #[inline]
     fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
@@ -221,6 +597,57 @@ where
     {
         HashMap::remove(self, k)
     }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        match HashMap::entry(self, k.into()) {
+            std::collections::hash_map::Entry::Occupied(e) => ObjectEntry::Occupied(e.into_mut()),
+            std::collections::hash_map::Entry::Vacant(e) => ObjectEntry::Vacant(e),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        HashMap::iter_mut(self)
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        HashMap::values_mut(self)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        HashMap::retain(self, f);
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        HashMap::drain(self)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        HashMap::clear(self);
+    }
+}
+
+impl<'a, MapK, MapE> VacantObjectEntry<'a>
+    for std::collections::hash_map::VacantEntry<'a, MapK, MapE>
+{
+    type Element = MapE;
+
+    #[inline]
+    fn insert(self, v: MapE) -> &'a mut MapE {
+        std::collections::hash_map::VacantEntry::insert(self, v)
+    }
 }
 
 #[cfg(feature = "hashbrown")]
@@ -230,6 +657,18 @@ where
 {
     type Key = MapK;
     type Element = MapE;
+    type Iter<'i>
+        = hashbrown::hash_map::Iter<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Keys<'i>
+        = hashbrown::hash_map::Keys<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Values<'i>
+        = hashbrown::hash_map::Values<'i, MapK, MapE>
+    where
+        Self: 'i;
 
     #[inline]
     fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
@@ -241,18 +680,18 @@ where
     }
 
     #[inline]
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&Self::Key, &Self::Element)> + 'i> {
-        Box::new(Hashbrown::iter(self))
+    fn iter(&self) -> Self::Iter<'_> {
+        Hashbrown::iter(self)
     }
 
     #[inline]
-    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Key> + 'i> {
-        Box::new(Hashbrown::keys(self))
+    fn keys(&self) -> Self::Keys<'_> {
+        Hashbrown::keys(self)
     }
 
     #[inline]
-    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Element> + 'i> {
-        Box::new(Hashbrown::values(self))
+    fn values(&self) -> Self::Values<'_> {
+        Hashbrown::values(self)
     }
 
     #[inline]
@@ -267,6 +706,22 @@ where
 {
     type Key = MapK;
     type Element = MapE;
+    type IterMut<'i>
+        = hashbrown::hash_map::IterMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = hashbrown::hash_map::ValuesMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Drain<'i>
+        = hashbrown::hash_map::Drain<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = hashbrown::hash_map::VacantEntry<'e, MapK, MapE, S>
+    where
+        Self: 'e;
 
     #[inline]
     fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
@@ -295,16 +750,82 @@ where
     {
         Hashbrown::remove(self, k)
     }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        match Hashbrown::entry(self, k.into()) {
+            hashbrown::hash_map::Entry::Occupied(e) => ObjectEntry::Occupied(e.into_mut()),
+            hashbrown::hash_map::Entry::Vacant(e) => ObjectEntry::Vacant(e),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        Hashbrown::iter_mut(self)
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        Hashbrown::values_mut(self)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        Hashbrown::retain(self, f);
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        Hashbrown::drain(self)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        Hashbrown::clear(self);
+    }
 }
 
-#[cfg(feature = "c-abi")]
-impl<MapK, MapE, S: ::std::hash::BuildHasher> Object
-    for abi_stable::std_types::RHashMap<MapK, MapE, S>
+#[cfg(feature = "hashbrown")]
+impl<'a, MapK, MapE, S> VacantObjectEntry<'a>
+    for hashbrown::hash_map::VacantEntry<'a, MapK, MapE, S>
+where
+    MapK: Hash,
+    S: BuildHasher,
+{
+    type Element = MapE;
+
+    #[inline]
+    fn insert(self, v: MapE) -> &'a mut MapE {
+        hashbrown::hash_map::VacantEntry::insert(self, v)
+    }
+}
+
+#[cfg(feature = "arena")]
+impl<MapK, MapE, S: BuildHasher> Object for Hashbrown<MapK, MapE, S, &bumpalo::Bump>
 where
     MapK: Hash + Eq,
 {
     type Key = MapK;
     type Element = MapE;
+    type Iter<'i>
+        = hashbrown::hash_map::Iter<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Keys<'i>
+        = hashbrown::hash_map::Keys<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Values<'i>
+        = hashbrown::hash_map::Values<'i, MapK, MapE>
+    where
+        Self: 'i;
 
     #[inline]
     fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
@@ -312,38 +833,52 @@ where
         Self::Key: Borrow<Q> + Hash + Eq,
         Q: ?Sized + Hash + Eq + Ord,
     {
-        abi_stable::std_types::RHashMap::get(self, k)
+        Hashbrown::get(self, k)
     }
 
     #[inline]
-    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&Self::Key, &Self::Element)> + 'i> {
-        Box::new(abi_stable::std_types::RHashMap::iter(self).map(Into::into))
+    fn iter(&self) -> Self::Iter<'_> {
+        Hashbrown::iter(self)
     }
 
     #[inline]
-    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Key> + 'i> {
-        Box::new(abi_stable::std_types::RHashMap::keys(self))
+    fn keys(&self) -> Self::Keys<'_> {
+        Hashbrown::keys(self)
     }
 
     #[inline]
-    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &Self::Element> + 'i> {
-        Box::new(abi_stable::std_types::RHashMap::values(self))
+    fn values(&self) -> Self::Values<'_> {
+        Hashbrown::values(self)
     }
 
     #[inline]
     fn len(&self) -> usize {
-        abi_stable::std_types::RHashMap::len(self)
+        Hashbrown::len(self)
     }
 }
-
-#[cfg(feature = "c-abi")]
-impl<MapK, MapE, S: ::std::hash::BuildHasher> ObjectMut
-    for abi_stable::std_types::RHashMap<MapK, MapE, S>
+#[cfg(feature = "arena")]
+impl<'b, MapK, MapE, S: BuildHasher> ObjectMut for Hashbrown<MapK, MapE, S, &'b bumpalo::Bump>
 where
     MapK: Hash + Eq,
 {
     type Key = MapK;
     type Element = MapE;
+    type IterMut<'i>
+        = hashbrown::hash_map::IterMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = hashbrown::hash_map::ValuesMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Drain<'i>
+        = hashbrown::hash_map::Drain<'i, MapK, MapE, &'b bumpalo::Bump>
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = hashbrown::hash_map::VacantEntry<'e, MapK, MapE, S, &'b bumpalo::Bump>
+    where
+        Self: 'e;
 
     #[inline]
     fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
@@ -351,7 +886,7 @@ where
         Self::Key: Borrow<Q> + Hash + Eq,
         Q: ?Sized + Hash + Eq + Ord,
     {
-        abi_stable::std_types::RHashMap::get_mut(self, k)
+        Hashbrown::get_mut(self, k)
     }
 
     #[inline]
@@ -361,7 +896,7 @@ where
         V: Into<Self::Element>,
         Self::Key: Hash + Eq,
     {
-        abi_stable::std_types::RHashMap::insert(self, k.into(), v.into()).into()
+        Hashbrown::insert(self, k.into(), v.into())
     }
 
     #[inline]
@@ -370,6 +905,1029 @@ where
         Self::Key: Borrow<Q> + Hash + Eq,
         Q: ?Sized + Hash + Eq + Ord,
     {
-        abi_stable::std_types::RHashMap::remove(self, k).into()
+        Hashbrown::remove(self, k)
+    }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        match Hashbrown::entry(self, k.into()) {
+            hashbrown::hash_map::Entry::Occupied(e) => ObjectEntry::Occupied(e.into_mut()),
+            hashbrown::hash_map::Entry::Vacant(e) => ObjectEntry::Vacant(e),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        Hashbrown::iter_mut(self)
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        Hashbrown::values_mut(self)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        Hashbrown::retain(self, f);
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        Hashbrown::drain(self)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        Hashbrown::clear(self);
+    }
+}
+
+#[cfg(feature = "arena")]
+impl<'a, MapK, MapE, S> VacantObjectEntry<'a>
+    for hashbrown::hash_map::VacantEntry<'a, MapK, MapE, S, &bumpalo::Bump>
+where
+    MapK: Hash,
+    S: BuildHasher,
+{
+    type Element = MapE;
+
+    #[inline]
+    fn insert(self, v: MapE) -> &'a mut MapE {
+        hashbrown::hash_map::VacantEntry::insert(self, v)
+    }
+}
+
+#[cfg(feature = "c-abi")]
+impl<MapK, MapE, S: ::std::hash::BuildHasher> Object
+    for abi_stable::std_types::RHashMap<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+{
+    type Key = MapK;
+    type Element = MapE;
+    type Iter<'i>
+        = std::iter::Map<
+        abi_stable::std_types::map::Iter<'i, MapK, MapE>,
+        fn(abi_stable::std_types::Tuple2<&'i MapK, &'i MapE>) -> (&'i MapK, &'i MapE),
+    >
+    where
+        Self: 'i;
+    type Keys<'i>
+        = std::iter::Map<
+        abi_stable::std_types::map::Iter<'i, MapK, MapE>,
+        fn(abi_stable::std_types::Tuple2<&'i MapK, &'i MapE>) -> &'i MapK,
+    >
+    where
+        Self: 'i;
+    type Values<'i>
+        = std::iter::Map<
+        abi_stable::std_types::map::Iter<'i, MapK, MapE>,
+        fn(abi_stable::std_types::Tuple2<&'i MapK, &'i MapE>) -> &'i MapE,
+    >
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        abi_stable::std_types::RHashMap::get(self, k)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        abi_stable::std_types::RHashMap::iter(self).map(Into::into)
+    }
+
+    #[inline]
+    fn keys(&self) -> Self::Keys<'_> {
+        abi_stable::std_types::RHashMap::iter(self).map(|e| e.0)
+    }
+
+    #[inline]
+    fn values(&self) -> Self::Values<'_> {
+        abi_stable::std_types::RHashMap::iter(self).map(|e| e.1)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        abi_stable::std_types::RHashMap::len(self)
+    }
+}
+
+#[cfg(feature = "c-abi")]
+impl<MapK, MapE, S: ::std::hash::BuildHasher> ObjectMut
+    for abi_stable::std_types::RHashMap<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+{
+    type Key = MapK;
+    type Element = MapE;
+    type IterMut<'i>
+        = std::iter::Map<
+        abi_stable::std_types::map::IterMut<'i, MapK, MapE>,
+        fn(abi_stable::std_types::Tuple2<&'i MapK, &'i mut MapE>) -> (&'i MapK, &'i mut MapE),
+    >
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = std::iter::Map<
+        abi_stable::std_types::map::IterMut<'i, MapK, MapE>,
+        fn(abi_stable::std_types::Tuple2<&'i MapK, &'i mut MapE>) -> &'i mut MapE,
+    >
+    where
+        Self: 'i;
+    type Drain<'i>
+        = std::iter::Map<
+        abi_stable::std_types::map::Drain<'i, MapK, MapE>,
+        fn(abi_stable::std_types::Tuple2<MapK, MapE>) -> (MapK, MapE),
+    >
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = abi_stable::std_types::map::RVacantEntry<'e, MapK, MapE>
+    where
+        Self: 'e;
+
+    #[inline]
+    fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        abi_stable::std_types::RHashMap::get_mut(self, k)
+    }
+
+    #[inline]
+    fn insert<K, V>(&mut self, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        abi_stable::std_types::RHashMap::insert(self, k.into(), v.into()).into()
+    }
+
+    #[inline]
+    fn remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        abi_stable::std_types::RHashMap::remove(self, k).into()
+    }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        match abi_stable::std_types::RHashMap::entry(self, k.into()) {
+            abi_stable::std_types::map::REntry::Occupied(e) => ObjectEntry::Occupied(e.into_mut()),
+            abi_stable::std_types::map::REntry::Vacant(e) => ObjectEntry::Vacant(e),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        abi_stable::std_types::RHashMap::iter_mut(self).map(Into::into)
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        abi_stable::std_types::RHashMap::iter_mut(self).map(|e| e.1)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        // `RHashMap` has no `retain` so we drain it and put back what we keep
+        let kept: Vec<(MapK, MapE)> = abi_stable::std_types::RHashMap::drain(self)
+            .map(Into::into)
+            .filter_map(|(k, mut v)| if f(&k, &mut v) { Some((k, v)) } else { None })
+            .collect();
+        for (k, v) in kept {
+            let _: abi_stable::std_types::ROption<MapE> =
+                abi_stable::std_types::RHashMap::insert(self, k, v);
+        }
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        abi_stable::std_types::RHashMap::drain(self).map(Into::into)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        abi_stable::std_types::RHashMap::clear(self);
+    }
+}
+
+#[cfg(feature = "c-abi")]
+impl<'a, MapK, MapE> VacantObjectEntry<'a>
+    for abi_stable::std_types::map::RVacantEntry<'a, MapK, MapE>
+{
+    type Element = MapE;
+
+    #[inline]
+    fn insert(self, v: MapE) -> &'a mut MapE {
+        abi_stable::std_types::map::RVacantEntry::insert(self, v)
+    }
+}
+
+impl<MapK, MapE> Object for BTreeMap<MapK, MapE>
+where
+    MapK: Ord,
+{
+    type Key = MapK;
+    type Element = MapE;
+    type Iter<'i>
+        = std::collections::btree_map::Iter<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Keys<'i>
+        = std::collections::btree_map::Keys<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Values<'i>
+        = std::collections::btree_map::Values<'i, MapK, MapE>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        BTreeMap::get(self, k)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        BTreeMap::iter(self)
+    }
+
+    #[inline]
+    fn keys(&self) -> Self::Keys<'_> {
+        BTreeMap::keys(self)
+    }
+
+    #[inline]
+    fn values(&self) -> Self::Values<'_> {
+        BTreeMap::values(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+}
+
+impl<MapK, MapE> ObjectMut for BTreeMap<MapK, MapE>
+where
+    MapK: Ord,
+{
+    type Key = MapK;
+    type Element = MapE;
+    type IterMut<'i>
+        = std::collections::btree_map::IterMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = std::collections::btree_map::ValuesMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Drain<'i>
+        = std::collections::btree_map::IntoIter<MapK, MapE>
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = std::collections::btree_map::VacantEntry<'e, MapK, MapE>
+    where
+        Self: 'e;
+
+    #[inline]
+    fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        BTreeMap::get_mut(self, k)
+    }
+
+    #[inline]
+    fn insert<K, V>(&mut self, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        BTreeMap::insert(self, k.into(), v.into())
+    }
+
+    #[inline]
+    fn remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        BTreeMap::remove(self, k)
+    }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        match BTreeMap::entry(self, k.into()) {
+            std::collections::btree_map::Entry::Occupied(e) => ObjectEntry::Occupied(e.into_mut()),
+            std::collections::btree_map::Entry::Vacant(e) => ObjectEntry::Vacant(e),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        BTreeMap::iter_mut(self)
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        BTreeMap::values_mut(self)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        BTreeMap::retain(self, f);
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        // `BTreeMap` has no `drain`, taking it leaves an empty map behind
+        std::mem::take(self).into_iter()
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        BTreeMap::clear(self);
+    }
+}
+
+impl<'a, MapK, MapE> VacantObjectEntry<'a>
+    for std::collections::btree_map::VacantEntry<'a, MapK, MapE>
+where
+    MapK: Ord,
+{
+    type Element = MapE;
+
+    #[inline]
+    fn insert(self, v: MapE) -> &'a mut MapE {
+        std::collections::btree_map::VacantEntry::insert(self, v)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<MapK, MapE, S: BuildHasher> Object for indexmap::IndexMap<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+{
+    type Key = MapK;
+    type Element = MapE;
+    type Iter<'i>
+        = indexmap::map::Iter<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Keys<'i>
+        = indexmap::map::Keys<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Values<'i>
+        = indexmap::map::Values<'i, MapK, MapE>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        indexmap::IndexMap::get(self, k)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        indexmap::IndexMap::iter(self)
+    }
+
+    #[inline]
+    fn keys(&self) -> Self::Keys<'_> {
+        indexmap::IndexMap::keys(self)
+    }
+
+    #[inline]
+    fn values(&self) -> Self::Values<'_> {
+        indexmap::IndexMap::values(self)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        indexmap::IndexMap::len(self)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<MapK, MapE, S: BuildHasher> ObjectMut for indexmap::IndexMap<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+{
+    type Key = MapK;
+    type Element = MapE;
+    type IterMut<'i>
+        = indexmap::map::IterMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = indexmap::map::ValuesMut<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type Drain<'i>
+        = indexmap::map::Drain<'i, MapK, MapE>
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = indexmap::map::VacantEntry<'e, MapK, MapE>
+    where
+        Self: 'e;
+
+    #[inline]
+    fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        indexmap::IndexMap::get_mut(self, k)
+    }
+
+    #[inline]
+    fn insert<K, V>(&mut self, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        indexmap::IndexMap::insert(self, k.into(), v.into())
+    }
+
+    #[inline]
+    fn remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        // keep the insertion order of the remaining keys
+        indexmap::IndexMap::shift_remove(self, k)
+    }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        match indexmap::IndexMap::entry(self, k.into()) {
+            indexmap::map::Entry::Occupied(e) => ObjectEntry::Occupied(e.into_mut()),
+            indexmap::map::Entry::Vacant(e) => ObjectEntry::Vacant(e),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        indexmap::IndexMap::iter_mut(self)
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        indexmap::IndexMap::values_mut(self)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        indexmap::IndexMap::retain(self, f);
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        indexmap::IndexMap::drain(self, ..)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        indexmap::IndexMap::clear(self);
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<'a, MapK, MapE> VacantObjectEntry<'a> for indexmap::map::VacantEntry<'a, MapK, MapE> {
+    type Element = MapE;
+
+    #[inline]
+    fn insert(self, v: MapE) -> &'a mut MapE {
+        indexmap::map::VacantEntry::insert(self, v)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<MapK, MapE, S: BuildHasher> OrderedObject for indexmap::IndexMap<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+{
+    #[inline]
+    fn get_index(&self, i: usize) -> Option<(&Self::Key, &Self::Element)> {
+        indexmap::IndexMap::get_index(self, i)
+    }
+
+    #[inline]
+    fn shift_remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        indexmap::IndexMap::shift_remove(self, k)
+    }
+
+    #[inline]
+    fn insert_at<K, V>(&mut self, i: usize, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        indexmap::IndexMap::shift_insert(self, i, k.into(), v.into())
+    }
+
+    #[inline]
+    fn move_index(&mut self, from: usize, to: usize) {
+        indexmap::IndexMap::move_index(self, from, to);
+    }
+
+    #[inline]
+    fn sort_keys(&mut self)
+    where
+        Self::Key: Ord,
+    {
+        indexmap::IndexMap::sort_keys(self);
+    }
+}
+
+/// A linear association list of key value pairs, lookups scan all keys
+/// which is fast for small objects and keeps the insertion order
+///
+/// This is a newtype around `Vec<(K, V)>` instead of implementing the
+/// object traits for the vector itself. With the prelude in scope the
+/// object methods `get`, `first` and `last` would shadow the slice methods
+/// of every `Vec` of pairs. Use `From`, `as_slice` and `into_inner` to
+/// convert between the two.
+///
+/// As an `OrderedObject` every operation is O(n):
+///
+/// * `insert_at` panics if `i` is greater than the number of pairs, or
+///   greater than the number of pairs minus one if the key already exists
+///   since it is removed first
+/// * `move_index` panics if `from` or `to` are not smaller than the number
+///   of pairs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssocList<K, V>(Vec<(K, V)>);
+
+impl<K, V> AssocList<K, V> {
+    /// Creates an empty list
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Creates an empty list with room for `capacity` pairs
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    /// The pairs in order
+    #[must_use]
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.0
+    }
+
+    /// Returns the underlying pairs
+    #[must_use]
+    pub fn into_inner(self) -> Vec<(K, V)> {
+        self.0
+    }
+}
+
+impl<K, V> Default for AssocList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for AssocList<K, V>
+where
+    K: Eq,
+{
+    /// Builds the list from pairs, for duplicate keys the last one wins
+    fn from(pairs: Vec<(K, V)>) -> Self {
+        pairs.into_iter().collect()
+    }
+}
+
+impl<K, V> From<AssocList<K, V>> for Vec<(K, V)> {
+    fn from(list: AssocList<K, V>) -> Self {
+        list.0
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for AssocList<K, V>
+where
+    K: Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for (k, v) in iter {
+            if let Some((_, old)) = list.0.iter_mut().find(|(key, _)| *key == k) {
+                *old = v;
+            } else {
+                list.0.push((k, v));
+            }
+        }
+        list
+    }
+}
+
+impl<K, V> IntoIterator for AssocList<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<MapK, MapE> Object for AssocList<MapK, MapE> {
+    type Key = MapK;
+    type Element = MapE;
+    type Iter<'i>
+        = std::iter::Map<
+        std::slice::Iter<'i, (MapK, MapE)>,
+        fn(&'i (MapK, MapE)) -> (&'i MapK, &'i MapE),
+    >
+    where
+        Self: 'i;
+    type Keys<'i>
+        = std::iter::Map<std::slice::Iter<'i, (MapK, MapE)>, fn(&'i (MapK, MapE)) -> &'i MapK>
+    where
+        Self: 'i;
+    type Values<'i>
+        = std::iter::Map<std::slice::Iter<'i, (MapK, MapE)>, fn(&'i (MapK, MapE)) -> &'i MapE>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        self.0
+            .iter()
+            .find(|(key, _)| key.borrow() == k)
+            .map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter().map(|(k, v)| (k, v))
+    }
+
+    #[inline]
+    fn keys(&self) -> Self::Keys<'_> {
+        self.0.iter().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn values(&self) -> Self::Values<'_> {
+        self.0.iter().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<MapK, MapE> ObjectMut for AssocList<MapK, MapE> {
+    type Key = MapK;
+    type Element = MapE;
+    type IterMut<'i>
+        = std::iter::Map<
+        std::slice::IterMut<'i, (MapK, MapE)>,
+        fn(&'i mut (MapK, MapE)) -> (&'i MapK, &'i mut MapE),
+    >
+    where
+        Self: 'i;
+    type ValuesMut<'i>
+        = std::iter::Map<
+        std::slice::IterMut<'i, (MapK, MapE)>,
+        fn(&'i mut (MapK, MapE)) -> &'i mut MapE,
+    >
+    where
+        Self: 'i;
+    type Drain<'i>
+        = std::vec::Drain<'i, (MapK, MapE)>
+    where
+        Self: 'i;
+    type VacantEntry<'e>
+        = VacantAssocEntry<'e, MapK, MapE>
+    where
+        Self: 'e;
+
+    #[inline]
+    fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        self.0
+            .iter_mut()
+            .find(|(key, _)| key.borrow() == k)
+            .map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn insert<K, V>(&mut self, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        let k = k.into();
+        if let Some((_, old)) = self.0.iter_mut().find(|(key, _)| *key == k) {
+            return Some(std::mem::replace(old, v.into()));
+        }
+        self.0.push((k, v.into()));
+        None
+    }
+
+    #[inline]
+    fn remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        let idx = self.0.iter().position(|(key, _)| key.borrow() == k)?;
+        Some(self.0.remove(idx).1)
+    }
+
+    #[inline]
+    fn entry<K>(&mut self, k: K) -> ObjectEntry<'_, Self::VacantEntry<'_>>
+    where
+        K: Into<Self::Key>,
+        Self::Key: Hash + Eq,
+    {
+        let k = k.into();
+        match self.0.iter().position(|(key, _)| *key == k) {
+            Some(idx) => ObjectEntry::Occupied(&mut self.0[idx].1),
+            None => ObjectEntry::Vacant(VacantAssocEntry {
+                list: &mut self.0,
+                key: k,
+            }),
+        }
+    }
+
+    #[inline]
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.0.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> Self::ValuesMut<'_> {
+        self.0.iter_mut().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Self::Key, &mut Self::Element) -> bool,
+    {
+        self.0.retain_mut(|(k, v)| f(k, v));
+    }
+
+    #[inline]
+    fn drain(&mut self) -> Self::Drain<'_> {
+        self.0.drain(..)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// The vacant entry of a list of key value pairs, inserting appends the pair
+pub struct VacantAssocEntry<'a, K, V> {
+    list: &'a mut Vec<(K, V)>,
+    key: K,
+}
+
+impl<'a, K, V> VacantObjectEntry<'a> for VacantAssocEntry<'a, K, V> {
+    type Element = V;
+
+    #[inline]
+    fn insert(self, v: V) -> &'a mut V {
+        let idx = self.list.len();
+        self.list.push((self.key, v));
+        &mut self.list[idx].1
+    }
+}
+
+impl<MapK, MapE> OrderedObject for AssocList<MapK, MapE> {
+    #[inline]
+    fn get_index(&self, i: usize) -> Option<(&Self::Key, &Self::Element)> {
+        self.0.get(i).map(|(k, v)| (k, v))
+    }
+
+    #[inline]
+    fn shift_remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        ObjectMut::remove(self, k)
+    }
+
+    #[inline]
+    fn insert_at<K, V>(&mut self, i: usize, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        let k = k.into();
+        if let Some(idx) = self.0.iter().position(|(key, _)| *key == k) {
+            let (k, old) = self.0.remove(idx);
+            self.0.insert(i, (k, v.into()));
+            Some(old)
+        } else {
+            self.0.insert(i, (k, v.into()));
+            None
+        }
+    }
+
+    #[inline]
+    fn move_index(&mut self, from: usize, to: usize) {
+        let pair = self.0.remove(from);
+        self.0.insert(to, pair);
+    }
+
+    #[inline]
+    fn sort_keys(&mut self)
+    where
+        Self::Key: Ord,
+    {
+        self.0.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AssocList, ObjectMut};
+    use std::collections::{BTreeMap, HashMap};
+
+    fn count<O>(o: &mut O, words: &[&str])
+    where
+        O: ObjectMut<Key = String, Element = u32>,
+    {
+        for w in words {
+            *o.entry(*w).or_insert(0) += 1;
+        }
+        o.entry("seen").and_modify(|v| *v += 10).or_default();
+    }
+
+    #[test]
+    fn entry() {
+        let words = ["a", "b", "a", "seen", "a"];
+        let mut m = HashMap::new();
+        count(&mut m, &words);
+        assert_eq!(m["a"], 3);
+        assert_eq!(m["b"], 1);
+        assert_eq!(m["seen"], 11);
+
+        let mut m = BTreeMap::new();
+        count(&mut m, &words);
+        assert_eq!(m["a"], 3);
+        assert_eq!(m["seen"], 11);
+
+        let mut m = AssocList::new();
+        count(&mut m, &words);
+        assert_eq!(
+            m.into_inner(),
+            vec![("a".into(), 3), ("b".into(), 1), ("seen".into(), 11)]
+        );
+    }
+
+    #[test]
+    fn drain_and_iter_mut() {
+        let mut m: HashMap<String, u32> = HashMap::new();
+        m.insert("a".into(), 1);
+        m.insert("b".into(), 2);
+        for v in ObjectMut::values_mut(&mut m) {
+            *v *= 2;
+        }
+        for (k, v) in ObjectMut::iter_mut(&mut m) {
+            if k == "a" {
+                *v += 1;
+            }
+        }
+        let mut drained: Vec<_> = ObjectMut::drain(&mut m).collect();
+        drained.sort();
+        assert_eq!(drained, vec![("a".into(), 3), ("b".into(), 4)]);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn pair_vec_keeps_slice_methods() {
+        #[allow(unused_imports)]
+        use crate::prelude::*;
+        let pairs = vec![("a", 1), ("b", 2)];
+        assert_eq!(
+            pairs.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert_eq!(ArrayTrait::get(&pairs, 1), Some(&("b", 2)));
+        assert_eq!(pairs.first(), Some(&("a", 1)));
+        assert_eq!(pairs.last(), Some(&("b", 2)));
+
+        let list: AssocList<&str, i32> = pairs.into_iter().chain(Some(("a", 3))).collect();
+        assert_eq!(ObjectTrait::get(&list, "a"), Some(&3));
+        assert_eq!(list.get_index(1), Some((&"b", &2)));
+        assert_eq!(ObjectTrait::len(&list), 2);
+    }
+
+    #[cfg(all(feature = "halfbrown", feature = "preserve_order"))]
+    #[test]
+    fn halfbrown_ordered() {
+        use super::OrderedObject;
+        let mut m: halfbrown::HashMap<String, u32> = halfbrown::HashMap::new();
+        for (k, v) in [("c", 0_u32), ("a", 1), ("d", 2), ("b", 3)] {
+            assert_eq!(ObjectMut::insert(&mut m, k, v), None);
+        }
+        assert_eq!(m.get_index(1), Some((&"a".to_string(), &1)));
+        assert_eq!(m.shift_remove("a"), Some(1));
+        assert_eq!(m.insert_at(0, "e", 9_u32), None);
+        m.move_index(0, 3);
+        let keys: Vec<&str> = m.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["c", "d", "b", "e"]);
+        m.sort_keys();
+        assert_eq!(m.first(), Some((&"b".to_string(), &3)));
+        assert_eq!(m.last(), Some((&"e".to_string(), &9)));
+    }
+
+    #[test]
+    fn concrete_iterators() {
+        use super::Object;
+        use std::collections::{btree_map, hash_map};
+        let m: HashMap<String, u8> = HashMap::from([("a".to_string(), 1)]);
+        let iter: hash_map::Iter<'_, String, u8> = Object::iter(&m);
+        let keys: hash_map::Keys<'_, String, u8> = Object::keys(&m);
+        let values: hash_map::Values<'_, String, u8> = Object::values(&m);
+        assert_eq!((iter.len(), keys.len(), values.len()), (1, 1, 1));
+        let b: BTreeMap<String, u8> = m.into_iter().collect();
+        let iter: btree_map::Iter<'_, String, u8> = Object::iter(&b);
+        // the iterators borrow the map, they can be sent with it
+        let pairs = std::thread::scope(|s| {
+            s.spawn(move || iter.map(|(k, v)| (k.clone(), *v)).collect::<Vec<_>>())
+                .join()
+                .expect("no panic")
+        });
+        assert_eq!(pairs, [("a".to_string(), 1)]);
     }
 }
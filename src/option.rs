@@ -167,7 +167,7 @@ where
 
 impl<V> TypedValue for &V
 where
-    V: TypedValue,
+    V: TypedValue + ?Sized,
 {
     fn value_type(&self) -> ValueType {
         (*self).value_type()
@@ -175,7 +175,7 @@ where
 }
 impl<V> ValueAsScalar for &V
 where
-    V: ValueAsScalar,
+    V: ValueAsScalar + ?Sized,
 {
     fn as_null(&self) -> Option<()> {
         (*self).as_null()
@@ -204,7 +204,7 @@ where
 
 impl<V> ValueAsContainer for &V
 where
-    V: ValueAsContainer,
+    V: ValueAsContainer + ?Sized,
 {
     type Array = V::Array;
     type Object = V::Object;
@@ -0,0 +1,579 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::{
+    array::ArrayMut, base::ValueAsMutContainer, object::ObjectMut, StaticNode, ValueBuilder,
+};
+
+/// Parser settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// Maximum nesting of arrays and objects
+    pub max_depth: usize,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self { max_depth: 128 }
+    }
+}
+
+/// The kinds of errors the parser can encounter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The input ended in the middle of a value
+    Eof,
+    /// A character that is not valid at this position
+    UnexpectedCharacter(char),
+    /// A malformed number
+    InvalidNumber,
+    /// An unknown escape sequence in a string
+    InvalidEscape,
+    /// A `\u` escape that is not a valid code point
+    InvalidUnicode,
+    /// An unescaped control character in a string
+    ControlCharacter,
+    /// Arrays and objects are nested deeper than the configured limit
+    DepthLimitExceeded,
+    /// There is data left after the value
+    TrailingData,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eof => write!(f, "Unexpected end of input"),
+            Self::UnexpectedCharacter(c) => write!(f, "Unexpected character {c:?}"),
+            Self::InvalidNumber => write!(f, "Invalid number"),
+            Self::InvalidEscape => write!(f, "Invalid escape sequence"),
+            Self::InvalidUnicode => write!(f, "Invalid unicode escape"),
+            Self::ControlCharacter => write!(f, "Unescaped control character in string"),
+            Self::DepthLimitExceeded => write!(f, "Nesting depth limit exceeded"),
+            Self::TrailingData => write!(f, "Trailing data after the value"),
+        }
+    }
+}
+
+/// An error encountered while parsing JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line of the error, starting at 1
+    pub line: usize,
+    /// Column (in characters) of the error, starting at 1
+    pub column: usize,
+    /// What went wrong
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a JSON document into a value using the default settings
+///
+/// # Errors
+///
+/// if the input is not a single valid JSON value
+pub fn parse<'input, V>(input: &'input str) -> Result<V, ParseError>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<Cow<'input, str>> + Hash + Eq,
+{
+    parse_with(input, ParserConfig::default())
+}
+
+/// Parses a JSON document into a value, strings without escapes are
+/// borrowed from the input
+///
+/// # Errors
+///
+/// if the input is not a single valid JSON value
+pub fn parse_with<'input, V>(input: &'input str, config: ParserConfig) -> Result<V, ParseError>
+where
+    V: ValueBuilder<'input> + ValueAsMutContainer,
+    V::Array: ArrayMut<Element = V>,
+    V::Object: ObjectMut<Element = V>,
+    <V::Object as ObjectMut>::Key: From<Cow<'input, str>> + Hash + Eq,
+{
+    let mut parser = Parser {
+        input,
+        idx: 0,
+        config,
+    };
+    parser
+        .document()
+        .map_err(|(idx, kind)| error_at(input, idx, kind))
+}
+
+/// Turns a byte offset into a line / column error
+pub(crate) fn error_at(input: &str, idx: usize, kind: ParseErrorKind) -> ParseError {
+    let before = input.get(..idx).unwrap_or(input);
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    ParseError {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        kind,
+    }
+}
+
+/// Converts a validated JSON number into its scalar, integers use the
+/// smallest fitting type and fall back to floats
+pub(crate) fn number(s: &str) -> Option<StaticNode> {
+    if !s.contains(['.', 'e', 'E']) {
+        if let Ok(n) = s.parse::<i64>() {
+            return Some(StaticNode::I64(n));
+        }
+        if let Ok(n) = s.parse::<u64>() {
+            return Some(StaticNode::U64(n));
+        }
+        #[cfg(feature = "128bit")]
+        if let Ok(n) = s.parse::<i128>() {
+            return Some(StaticNode::I128(n));
+        }
+        #[cfg(feature = "128bit")]
+        if let Ok(n) = s.parse::<u128>() {
+            return Some(StaticNode::U128(n));
+        }
+    }
+    s.parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .map(StaticNode::F64)
+}
+
+/// Returns the length of the JSON number at the start of `bytes` or the
+/// offset of the first invalid byte
+pub(crate) fn number_len(bytes: &[u8]) -> Result<usize, usize> {
+    let digits = |i: usize| {
+        bytes
+            .get(i..)
+            .map_or(0, |b| b.iter().take_while(|b| b.is_ascii_digit()).count())
+    };
+    let mut i = usize::from(bytes.first() == Some(&b'-'));
+    match bytes.get(i) {
+        Some(b'0') => i += 1,
+        Some(b'1'..=b'9') => i += digits(i),
+        _ => return Err(i),
+    }
+    if bytes.get(i) == Some(&b'.') {
+        let n = digits(i + 1);
+        if n == 0 {
+            return Err(i + 1);
+        }
+        i += 1 + n;
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        let n = digits(i);
+        if n == 0 {
+            return Err(i);
+        }
+        i += n;
+    }
+    Ok(i)
+}
+
+/// Decodes the escape sequence following a backslash in `bytes`, returns the
+/// character and the number of bytes consumed or the kind of error and its
+/// offset. Surrogate pairs are joined.
+pub(crate) fn unescape(bytes: &[u8]) -> Result<(char, usize), (ParseErrorKind, usize)> {
+    let c = match bytes.first() {
+        Some(b'"') => '"',
+        Some(b'\\') => '\\',
+        Some(b'/') => '/',
+        Some(b'b') => '\u{8}',
+        Some(b'f') => '\u{c}',
+        Some(b'n') => '\n',
+        Some(b'r') => '\r',
+        Some(b't') => '\t',
+        Some(b'u') => {
+            let high = hex4(bytes, 1)?;
+            if !(0xD800..0xDC00).contains(&high) {
+                return char::from_u32(high)
+                    .map(|c| (c, 5))
+                    .ok_or((ParseErrorKind::InvalidUnicode, 0));
+            }
+            if bytes.get(5..7) != Some(b"\\u") {
+                return Err((ParseErrorKind::InvalidUnicode, 5));
+            }
+            let low = hex4(bytes, 7)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err((ParseErrorKind::InvalidUnicode, 7));
+            }
+            let c = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+            return char::from_u32(c)
+                .map(|c| (c, 11))
+                .ok_or((ParseErrorKind::InvalidUnicode, 0));
+        }
+        Some(_) => return Err((ParseErrorKind::InvalidEscape, 0)),
+        None => return Err((ParseErrorKind::Eof, 0)),
+    };
+    Ok((c, 1))
+}
+
+/// Reads four hex digits, a short escape is only an `Eof` if the input
+/// actually ends
+fn hex4(bytes: &[u8], start: usize) -> Result<u32, (ParseErrorKind, usize)> {
+    (start..start + 4).try_fold(0, |n, i| {
+        let b = bytes.get(i).ok_or((ParseErrorKind::Eof, i))?;
+        let d = char::from(*b)
+            .to_digit(16)
+            .ok_or((ParseErrorKind::InvalidUnicode, i))?;
+        Ok(n << 4 | d)
+    })
+}
+
+/// Errors carry the byte offset until they are turned into a `ParseError`
+type ParserResult<T> = Result<T, (usize, ParseErrorKind)>;
+
+struct Parser<'input> {
+    input: &'input str,
+    idx: usize,
+    config: ParserConfig,
+}
+
+impl<'input> Parser<'input> {
+    fn bytes(&self) -> &'input [u8] {
+        self.input.as_bytes()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(
+            self.bytes().get(self.idx),
+            Some(b' ' | b'\t' | b'\n' | b'\r')
+        ) {
+            self.idx += 1;
+        }
+    }
+
+    /// Returns the next non whitespace byte without consuming it
+    fn peek(&mut self) -> ParserResult<u8> {
+        self.skip_whitespace();
+        self.bytes()
+            .get(self.idx)
+            .copied()
+            .ok_or((self.idx, ParseErrorKind::Eof))
+    }
+
+    fn unexpected(&self) -> (usize, ParseErrorKind) {
+        self.input[self.idx..]
+            .chars()
+            .next()
+            .map_or((self.idx, ParseErrorKind::Eof), |c| {
+                (self.idx, ParseErrorKind::UnexpectedCharacter(c))
+            })
+    }
+
+    fn expect(&mut self, b: u8) -> ParserResult<()> {
+        if self.peek()? == b {
+            self.idx += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn document<V>(&mut self) -> ParserResult<V>
+    where
+        V: ValueBuilder<'input> + ValueAsMutContainer,
+        V::Array: ArrayMut<Element = V>,
+        V::Object: ObjectMut<Element = V>,
+        <V::Object as ObjectMut>::Key: From<Cow<'input, str>> + Hash + Eq,
+    {
+        let value = self.value(0)?;
+        self.skip_whitespace();
+        if self.idx == self.input.len() {
+            Ok(value)
+        } else {
+            Err((self.idx, ParseErrorKind::TrailingData))
+        }
+    }
+
+    fn value<V>(&mut self, depth: usize) -> ParserResult<V>
+    where
+        V: ValueBuilder<'input> + ValueAsMutContainer,
+        V::Array: ArrayMut<Element = V>,
+        V::Object: ObjectMut<Element = V>,
+        <V::Object as ObjectMut>::Key: From<Cow<'input, str>> + Hash + Eq,
+    {
+        match self.peek()? {
+            b'{' | b'[' if depth >= self.config.max_depth => {
+                Err((self.idx, ParseErrorKind::DepthLimitExceeded))
+            }
+            b'{' => {
+                self.idx += 1;
+                let mut object = V::object();
+                if self.peek()? == b'}' {
+                    self.idx += 1;
+                    return Ok(object);
+                }
+                loop {
+                    if self.peek()? != b'"' {
+                        return Err(self.unexpected());
+                    }
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    let value: V = self.value(depth + 1)?;
+                    if let Some(object) = object.as_object_mut() {
+                        let _: Option<_> = object.insert(key, value);
+                    }
+                    match self.peek()? {
+                        b',' => self.idx += 1,
+                        b'}' => break,
+                        _ => return Err(self.unexpected()),
+                    }
+                }
+                self.idx += 1;
+                Ok(object)
+            }
+            b'[' => {
+                self.idx += 1;
+                let mut array = V::array();
+                if self.peek()? == b']' {
+                    self.idx += 1;
+                    return Ok(array);
+                }
+                loop {
+                    let value: V = self.value(depth + 1)?;
+                    if let Some(array) = array.as_array_mut() {
+                        array.push(value);
+                    }
+                    match self.peek()? {
+                        b',' => self.idx += 1,
+                        b']' => break,
+                        _ => return Err(self.unexpected()),
+                    }
+                }
+                self.idx += 1;
+                Ok(array)
+            }
+            b'"' => self.string().map(V::from),
+            b't' => self.literal("true", V::from(true)),
+            b'f' => self.literal("false", V::from(false)),
+            b'n' => self.literal("null", V::null()),
+            b'-' | b'0'..=b'9' => {
+                let start = self.idx;
+                let len =
+                    number_len(&self.bytes()[start..]).map_err(|i| self.number_error(start + i))?;
+                self.idx += len;
+                number(&self.input[start..self.idx])
+                    .map(V::from)
+                    .ok_or((start, ParseErrorKind::InvalidNumber))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn number_error(&self, idx: usize) -> (usize, ParseErrorKind) {
+        if idx == self.input.len() {
+            (idx, ParseErrorKind::Eof)
+        } else {
+            (idx, ParseErrorKind::InvalidNumber)
+        }
+    }
+
+    fn literal<V>(&mut self, literal: &str, value: V) -> ParserResult<V> {
+        for b in literal.bytes() {
+            if self.bytes().get(self.idx) != Some(&b) {
+                return Err(self.unexpected());
+            }
+            self.idx += 1;
+        }
+        Ok(value)
+    }
+
+    /// Parses a string starting at the opening quote
+    fn string(&mut self) -> ParserResult<Cow<'input, str>> {
+        self.idx += 1;
+        let start = self.idx;
+        let bytes = self.bytes();
+        loop {
+            match bytes.get(self.idx) {
+                Some(b'"') => {
+                    let s = &self.input[start..self.idx];
+                    self.idx += 1;
+                    return Ok(Cow::Borrowed(s));
+                }
+                Some(b'\\') => break,
+                Some(0..=0x1f) => return Err((self.idx, ParseErrorKind::ControlCharacter)),
+                Some(_) => self.idx += 1,
+                None => return Err((self.idx, ParseErrorKind::Eof)),
+            }
+        }
+        // only strings with escapes need to be copied
+        let mut s = String::from(&self.input[start..self.idx]);
+        loop {
+            match bytes.get(self.idx) {
+                Some(b'"') => {
+                    self.idx += 1;
+                    return Ok(Cow::Owned(s));
+                }
+                Some(b'\\') => {
+                    let (c, len) = unescape(&bytes[self.idx + 1..])
+                        .map_err(|(kind, i)| (self.idx + 1 + i, kind))?;
+                    s.push(c);
+                    self.idx += 1 + len;
+                }
+                Some(0..=0x1f) => return Err((self.idx, ParseErrorKind::ControlCharacter)),
+                Some(_) => {
+                    // copy everything up to the next quote or escape at once
+                    let end = bytes[self.idx..]
+                        .iter()
+                        .position(|b| matches!(b, b'"' | b'\\' | 0..=0x1f))
+                        .map_or(bytes.len(), |i| self.idx + i);
+                    s.push_str(&self.input[self.idx..end]);
+                    self.idx = end;
+                }
+                None => return Err((self.idx, ParseErrorKind::Eof)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, parse_with, ParseError, ParseErrorKind, ParserConfig};
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+
+    fn err(input: &str) -> ParseError {
+        parse::<NativeValue>(input).expect_err("invalid JSON")
+    }
+
+    fn document() -> NativeValue {
+        let mut object = HashMap::new();
+        object.insert("ke\"y".to_string(), NativeValue::from("va\\lue ☃ \u{1}"));
+        object.insert("list".to_string(), NativeValue::from(vec![1.5, -2.25]));
+        object.insert(
+            "empty".to_string(),
+            NativeValue::from(HashMap::<String, NativeValue>::new()),
+        );
+        NativeValue::from(vec![
+            NativeValue::from(object),
+            NativeValue::null(),
+            NativeValue::from(true),
+            NativeValue::from(-3_i64),
+            NativeValue::from(u64::MAX),
+            NativeValue::from(Vec::<u64>::new()),
+        ])
+    }
+
+    #[test]
+    fn round_trip() {
+        let value = document();
+        assert_eq!(parse::<NativeValue>(&value.encode()), Ok(value.clone()));
+        assert_eq!(parse::<NativeValue>(&value.encode_pp()), Ok(value));
+    }
+
+    #[test]
+    fn numbers() {
+        let parsed = |s: &str| parse::<NativeValue>(s).expect("valid number");
+        assert_eq!(parsed("0").as_i64(), Some(0));
+        assert_eq!(parsed("-0").as_i64(), Some(0));
+        assert_eq!(parsed("-9223372036854775808").as_i64(), Some(i64::MIN));
+        assert_eq!(parsed("18446744073709551615").as_u64(), Some(u64::MAX));
+        assert_eq!(parsed("1e3").as_f64(), Some(1000.0));
+        assert_eq!(parsed("-1.5E-1").as_f64(), Some(-0.15));
+        assert_eq!(parsed(" 12 ").as_u8(), Some(12));
+        #[cfg(feature = "128bit")]
+        assert_eq!(
+            parsed("18446744073709551616").as_u128(),
+            Some(u128::from(u64::MAX) + 1)
+        );
+        #[cfg(not(feature = "128bit"))]
+        assert_eq!(
+            parsed("18446744073709551616").as_f64(),
+            Some(1.844_674_407_370_955_2e19)
+        );
+    }
+
+    #[test]
+    fn strings() {
+        let value = parse::<NativeValue>(r#""\"\\\/\b\f\n\r\t\u00e9\ud83d\ude00 ü""#)
+            .expect("valid string");
+        assert_eq!(value.as_str(), Some("\"\\/\u{8}\u{c}\n\r\té😀 ü"));
+        let value = parse::<NativeValue>(r#"{"a\u0062":"c"}"#).expect("valid object");
+        assert_eq!(value.get("ab").and_then(ValueAsScalar::as_str), Some("c"));
+    }
+
+    #[test]
+    fn malformed() {
+        for (input, line, column, kind) in [
+            ("", 1, 1, ParseErrorKind::Eof),
+            ("  ", 1, 3, ParseErrorKind::Eof),
+            ("[1,]", 1, 4, ParseErrorKind::UnexpectedCharacter(']')),
+            ("[1 2]", 1, 4, ParseErrorKind::UnexpectedCharacter('2')),
+            ("{\"a\" 1}", 1, 6, ParseErrorKind::UnexpectedCharacter('1')),
+            ("{1:1}", 1, 2, ParseErrorKind::UnexpectedCharacter('1')),
+            ("{\"a\":1", 1, 7, ParseErrorKind::Eof),
+            ("01", 1, 2, ParseErrorKind::TrailingData),
+            ("[1] 2", 1, 5, ParseErrorKind::TrailingData),
+            ("1.", 1, 3, ParseErrorKind::Eof),
+            ("1.x", 1, 3, ParseErrorKind::InvalidNumber),
+            ("-", 1, 2, ParseErrorKind::Eof),
+            ("+1", 1, 1, ParseErrorKind::UnexpectedCharacter('+')),
+            ("1e400", 1, 1, ParseErrorKind::InvalidNumber),
+            ("tru", 1, 4, ParseErrorKind::Eof),
+            ("nul!", 1, 4, ParseErrorKind::UnexpectedCharacter('!')),
+            ("\"abc", 1, 5, ParseErrorKind::Eof),
+            ("\"\\x\"", 1, 3, ParseErrorKind::InvalidEscape),
+            ("\"\\u12\"", 1, 6, ParseErrorKind::InvalidUnicode),
+            ("\"\\u12", 1, 6, ParseErrorKind::Eof),
+            ("\"\\ud800\"", 1, 8, ParseErrorKind::InvalidUnicode),
+            ("\"\\ud800\\u0041\"", 1, 10, ParseErrorKind::InvalidUnicode),
+            ("\"a\nb\"", 1, 3, ParseErrorKind::ControlCharacter),
+            (
+                "[\n  1,\n  ☃]",
+                3,
+                3,
+                ParseErrorKind::UnexpectedCharacter('☃'),
+            ),
+        ] {
+            let e = err(input);
+            assert_eq!(
+                (e.line, e.column, e.kind),
+                (line, column, kind),
+                "{input:?}"
+            );
+        }
+        assert_eq!(
+            err("[1,]").to_string(),
+            "Unexpected character ']' at line 1 column 4"
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        let input = "[".repeat(1_000_000);
+        let e = err(&input);
+        assert_eq!(
+            (e.column, e.kind),
+            (129, ParseErrorKind::DepthLimitExceeded)
+        );
+
+        let config = ParserConfig { max_depth: 2 };
+        assert!(parse_with::<NativeValue>("[{\"a\":1}]", config).is_ok());
+        for (input, column) in [("[[[1]]]", 3), (r#"{"a":{"b":{}}}"#, 11)] {
+            let e = parse_with::<NativeValue>(input, config).expect_err("too deep");
+            assert_eq!(
+                (e.column, e.kind),
+                (column, ParseErrorKind::DepthLimitExceeded)
+            );
+        }
+    }
+}
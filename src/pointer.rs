@@ -0,0 +1,344 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{
+    base::{
+        TypedValue, ValueAsContainer, ValueAsMutContainer, ValueAsScalar, ValueIntoContainer,
+        ValueIntoString, Writable,
+    },
+    ValueType,
+};
+
+/// Forwards the read only traits to the pointed to value
+macro_rules! forward_read {
+    ($($ty:ty $(where $bound:path)?),*) => {
+        $(
+            impl<V> TypedValue for $ty
+            where
+                V: TypedValue $(+ $bound)?,
+            {
+                #[inline]
+                fn value_type(&self) -> ValueType {
+                    (**self).value_type()
+                }
+            }
+
+            impl<V> ValueAsScalar for $ty
+            where
+                V: ValueAsScalar $(+ $bound)?,
+            {
+                #[inline]
+                fn as_null(&self) -> Option<()> {
+                    (**self).as_null()
+                }
+
+                #[inline]
+                fn as_bool(&self) -> Option<bool> {
+                    (**self).as_bool()
+                }
+
+                #[inline]
+                fn as_i64(&self) -> Option<i64> {
+                    (**self).as_i64()
+                }
+
+                #[inline]
+                fn as_i128(&self) -> Option<i128> {
+                    (**self).as_i128()
+                }
+
+                #[inline]
+                fn as_u64(&self) -> Option<u64> {
+                    (**self).as_u64()
+                }
+
+                #[inline]
+                fn as_u128(&self) -> Option<u128> {
+                    (**self).as_u128()
+                }
+
+                #[inline]
+                fn as_f64(&self) -> Option<f64> {
+                    (**self).as_f64()
+                }
+
+                #[inline]
+                fn cast_f64(&self) -> Option<f64> {
+                    (**self).cast_f64()
+                }
+
+                #[inline]
+                fn as_str(&self) -> Option<&str> {
+                    (**self).as_str()
+                }
+            }
+
+            impl<V> ValueAsContainer for $ty
+            where
+                V: ValueAsContainer $(+ $bound)?,
+            {
+                type Array = V::Array;
+                type Object = V::Object;
+
+                #[inline]
+                fn as_array(&self) -> Option<&Self::Array> {
+                    (**self).as_array()
+                }
+
+                #[inline]
+                fn as_object(&self) -> Option<&Self::Object> {
+                    (**self).as_object()
+                }
+            }
+
+            impl<V> Writable for $ty
+            where
+                V: Writable $(+ $bound)?,
+            {
+                #[inline]
+                fn encode(&self) -> String {
+                    (**self).encode()
+                }
+
+                #[inline]
+                fn encode_pp(&self) -> String {
+                    (**self).encode_pp()
+                }
+
+                #[inline]
+                fn write<'writer, W>(&self, w: &mut W) -> io::Result<()>
+                where
+                    W: 'writer + Write,
+                {
+                    (**self).write(w)
+                }
+
+                #[inline]
+                fn write_pp<'writer, W>(&self, w: &mut W) -> io::Result<()>
+                where
+                    W: 'writer + Write,
+                {
+                    (**self).write_pp(w)
+                }
+            }
+        )*
+    };
+}
+
+forward_read!(Box<V>, Rc<V>, Arc<V>, &mut V, Cow<'_, V> where Clone);
+
+/// Forwards mutable access, only for pointers that own or exclusively
+/// borrow the value
+macro_rules! forward_mut {
+    ($($ty:ty),*) => {
+        $(
+            impl<V> ValueAsMutContainer for $ty
+            where
+                V: ValueAsMutContainer,
+            {
+                type Array = V::Array;
+                type Object = V::Object;
+
+                #[inline]
+                fn as_array_mut(&mut self) -> Option<&mut Self::Array> {
+                    (**self).as_array_mut()
+                }
+
+                #[inline]
+                fn as_object_mut(&mut self) -> Option<&mut Self::Object> {
+                    (**self).as_object_mut()
+                }
+            }
+        )*
+    };
+}
+
+forward_mut!(Box<V>, &mut V);
+
+impl<V> ValueAsMutContainer for Cow<'_, V>
+where
+    V: ValueAsMutContainer + Clone,
+{
+    type Array = V::Array;
+    type Object = V::Object;
+
+    /// Clones a borrowed value before handing out the mutable reference
+    #[inline]
+    fn as_array_mut(&mut self) -> Option<&mut Self::Array> {
+        self.to_mut().as_array_mut()
+    }
+
+    /// Clones a borrowed value before handing out the mutable reference
+    #[inline]
+    fn as_object_mut(&mut self) -> Option<&mut Self::Object> {
+        self.to_mut().as_object_mut()
+    }
+}
+
+impl<V> ValueIntoString for Box<V>
+where
+    V: ValueIntoString,
+{
+    type String = V::String;
+
+    #[inline]
+    fn into_string(self) -> Option<Self::String> {
+        (*self).into_string()
+    }
+}
+
+impl<V> ValueIntoContainer for Box<V>
+where
+    V: ValueIntoContainer,
+{
+    type Array = V::Array;
+    type Object = V::Object;
+
+    #[inline]
+    fn into_array(self) -> Option<Self::Array> {
+        (*self).into_array()
+    }
+
+    #[inline]
+    fn into_object(self) -> Option<Self::Object> {
+        (*self).into_object()
+    }
+}
+
+impl<V> ValueIntoString for Cow<'_, V>
+where
+    V: ValueIntoString + Clone,
+{
+    type String = V::String;
+
+    /// Clones a borrowed value
+    #[inline]
+    fn into_string(self) -> Option<Self::String> {
+        self.into_owned().into_string()
+    }
+}
+
+impl<V> ValueIntoContainer for Cow<'_, V>
+where
+    V: ValueIntoContainer + Clone,
+{
+    type Array = V::Array;
+    type Object = V::Object;
+
+    /// Clones a borrowed value
+    #[inline]
+    fn into_array(self) -> Option<Self::Array> {
+        self.into_owned().into_array()
+    }
+
+    /// Clones a borrowed value
+    #[inline]
+    fn into_object(self) -> Option<Self::Object> {
+        self.into_owned().into_object()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    fn value() -> NativeValue {
+        NativeValue::from(HashMap::from([(
+            "a".to_string(),
+            NativeValue::from(vec![1_u8, 2]),
+        )]))
+    }
+
+    fn read<V>(v: &V, value: &NativeValue)
+    where
+        V: TypedValue
+            + ValueAsScalar
+            + ValueAsContainer<Array = Vec<NativeValue>, Object = HashMap<String, NativeValue>>
+            + Writable,
+    {
+        assert_eq!(v.value_type(), ValueType::Object);
+        assert_eq!(ValueAsScalar::as_str(v), None);
+        assert_eq!(v.as_array(), None);
+        assert_eq!(v.as_object(), value.as_object());
+        assert_eq!(v.encode(), value.encode());
+        assert_eq!(v.encode_pp(), value.encode_pp());
+        let mut out = Vec::new();
+        v.write(&mut out).expect("write");
+        assert_eq!(out, value.encode().into_bytes());
+        out.clear();
+        v.write_pp(&mut out).expect("write_pp");
+        assert_eq!(out, value.encode_pp().into_bytes());
+    }
+
+    #[test]
+    fn read_forwarding() {
+        let v = value();
+        read(&Box::new(v.clone()), &v);
+        read(&Rc::new(v.clone()), &v);
+        read(&Arc::new(v.clone()), &v);
+        read(&&mut v.clone(), &v);
+        read(&Cow::Borrowed(&v), &v);
+        read(&Cow::<NativeValue>::Owned(v.clone()), &v);
+
+        let s = Box::new(NativeValue::from("s"));
+        assert_eq!(s.value_type(), ValueType::String);
+        assert_eq!(ValueAsScalar::as_str(&s), Some("s"));
+        assert_eq!(Rc::new(1_u64).as_u64(), Some(1));
+        assert_eq!(Arc::new(-1_i64).cast_f64(), Some(-1.0));
+    }
+
+    #[test]
+    fn mut_forwarding() {
+        let mut b = Box::new(NativeValue::from(vec![1_u8]));
+        b.as_array_mut()
+            .expect("array")
+            .push(NativeValue::from(2_u8));
+        assert_eq!(b.as_array().map(Vec::len), Some(2));
+        assert!(b.as_object_mut().is_none());
+
+        let mut v = value();
+        {
+            let mut r = &mut v;
+            <&mut NativeValue as ValueAsMutContainer>::as_object_mut(&mut r)
+                .expect("object")
+                .insert("b".to_string(), NativeValue::from(true));
+        }
+        assert_eq!(v.as_object().map(HashMap::len), Some(2));
+
+        let original = value();
+        let mut c = Cow::Borrowed(&original);
+        c.as_object_mut()
+            .expect("object")
+            .insert("b".to_string(), NativeValue::from(true));
+        assert!(matches!(c, Cow::Owned(_)));
+        assert_eq!(c.as_object().map(HashMap::len), Some(2));
+        assert_eq!(original, value());
+    }
+
+    #[test]
+    fn into_forwarding() {
+        let s = Box::new(NativeValue::from("s"));
+        assert_eq!(s.into_string().as_deref(), Some("s"));
+        let a = Box::new(NativeValue::from(vec![1_u8]));
+        assert_eq!(a.clone().into_object(), None);
+        assert_eq!(a.into_array().map(|a| a.len()), Some(1));
+
+        let v = value();
+        assert_eq!(Cow::Borrowed(&v).into_string(), None);
+        assert_eq!(Cow::Borrowed(&v).into_array(), None);
+        assert_eq!(Cow::Borrowed(&v).into_object(), v.clone().into_object());
+        assert_eq!(
+            Cow::<NativeValue>::Owned(NativeValue::from("o"))
+                .into_string()
+                .as_deref(),
+            Some("o")
+        );
+    }
+}
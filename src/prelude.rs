@@ -1,6 +1,9 @@
 pub use crate::array::{Array as ArrayTrait, ArrayMut};
 pub use crate::base::*;
+pub use crate::convert::{FromValue, FromValueOwned, ValueParse};
 pub use crate::derived::*;
 pub use crate::generator::BaseGenerator;
-pub use crate::object::{Object as ObjectTrait, ObjectMut};
+pub use crate::object::{
+    Object as ObjectTrait, ObjectEntry, ObjectMut, OrderedObject, VacantObjectEntry,
+};
 pub use crate::*;
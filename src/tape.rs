@@ -0,0 +1,614 @@
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, Write};
+
+use crate::{
+    array::Array,
+    base::{TypedValue, ValueAsContainer, ValueAsScalar, Writable},
+    generator::{
+        BaseGenerator, DumpGenerator, Node, PrettyGenerator, PrettyWriterGenerator, WriterGenerator,
+    },
+    object::Object,
+    tokenizer::{Token, Tokenizer, TokenizerError},
+    StaticNode, ValueType,
+};
+
+/// A document stored as a single flat vector of nodes. The children of
+/// every array and object are stored next to each other so lookups only
+/// touch one contiguous slice.
+///
+/// The tape is built in one pass, the values of open containers are kept
+/// on a reused stack and moved onto the tape when their container ends, so
+/// children come before their container and the root is the last node.
+///
+/// Containers hold a raw pointer to their first child. All of them are
+/// derived from the base pointer of the node vector after the last node was
+/// pushed and stay valid because:
+///
+/// * the vector is never pushed to, resized or modified afterwards,
+/// * moving the `Tape` doesn't move the heap allocation of the vector,
+/// * `Tape` doesn't implement `Clone`, a copy would point into the original,
+/// * a `TapeValue` can't be constructed or moved out of a tape, it is only
+///   handed out by reference while the tape is borrowed.
+///
+/// For the same reasons the nodes can be shared between threads, which is
+/// why `TapeValue` implements `Send` and `Sync`.
+pub struct Tape<'input> {
+    nodes: Vec<TapeValue<'input>>,
+}
+
+/// A value stored on a `Tape`, it is only ever handed out as a reference
+/// into the tape
+pub struct TapeValue<'input>(Entry<'input>);
+
+enum Entry<'input> {
+    Static(StaticNode),
+    String(Cow<'input, str>),
+    Array(Children<'input>),
+    /// keys and values alternate
+    Object(Children<'input>),
+}
+
+/// The nodes of a container, they point into the tape that holds the
+/// container itself
+struct Children<'input> {
+    first: *const TapeValue<'input>,
+    len: usize,
+}
+
+// SAFETY: the pointers only refer to nodes of the same tape and the tape is
+// never modified once it is built, see `Tape`
+unsafe impl Send for TapeValue<'_> {}
+unsafe impl Sync for TapeValue<'_> {}
+
+/// The array view of a `TapeValue`
+#[repr(transparent)]
+pub struct TapeArray<'input>(TapeValue<'input>);
+
+/// The object view of a `TapeValue`
+#[repr(transparent)]
+pub struct TapeObject<'input>(TapeValue<'input>);
+
+/// Lays out a tape from a stream of values and container boundaries
+#[derive(Default)]
+struct Builder<'input> {
+    nodes: Vec<TapeValue<'input>>,
+    /// values of the open containers with the index of their first child
+    /// on the tape if they are containers themselves
+    pending: Vec<(TapeValue<'input>, usize)>,
+    /// start of every open container in `pending` and if it is an object
+    open: Vec<(usize, bool)>,
+    /// containers on the tape and the index of their first child
+    links: Vec<(usize, usize)>,
+    /// keys of the object that is being closed, reused between objects
+    keys: HashMap<Cow<'input, str>, usize>,
+}
+
+impl<'input> Builder<'input> {
+    fn value(&mut self, entry: Entry<'input>) {
+        self.pending.push((TapeValue(entry), 0));
+    }
+
+    fn start(&mut self, object: bool) {
+        self.open.push((self.pending.len(), object));
+    }
+
+    fn end(&mut self) {
+        let Some((start, object)) = self.open.pop() else {
+            return;
+        };
+        let first = self.nodes.len();
+        let mut values = self.pending.drain(start..);
+        if object {
+            self.keys.clear();
+            while let (Some((key, _)), Some(value)) = (values.next(), values.next()) {
+                let k = if let Entry::String(k) = &key.0 {
+                    k.clone()
+                } else {
+                    Cow::Borrowed("")
+                };
+                // the last duplicate key wins and keeps the first position,
+                // the replaced value stays on the tape unreachable
+                if let Some(&idx) = self.keys.get(&k) {
+                    Self::place(&mut self.nodes, &mut self.links, Some(idx + 1), value);
+                } else {
+                    self.keys.insert(k, self.nodes.len());
+                    self.nodes.push(key);
+                    Self::place(&mut self.nodes, &mut self.links, None, value);
+                }
+            }
+            drop(values);
+        } else {
+            for value in values {
+                Self::place(&mut self.nodes, &mut self.links, None, value);
+            }
+        }
+        let children = Children {
+            first: std::ptr::null(),
+            len: self.nodes.len() - first,
+        };
+        let entry = if object {
+            Entry::Object(children)
+        } else {
+            Entry::Array(children)
+        };
+        self.pending.push((TapeValue(entry), first));
+    }
+
+    /// Puts a value on the tape, at `at` or the end
+    fn place(
+        nodes: &mut Vec<TapeValue<'input>>,
+        links: &mut Vec<(usize, usize)>,
+        at: Option<usize>,
+        (value, first): (TapeValue<'input>, usize),
+    ) {
+        let idx = at.unwrap_or(nodes.len());
+        if let Entry::Array(_) | Entry::Object(_) = value.0 {
+            links.push((idx, first));
+        }
+        if idx == nodes.len() {
+            nodes.push(value);
+        } else {
+            nodes[idx] = value;
+        }
+    }
+
+    fn copy<V>(&mut self, value: &'input V)
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        let s = match Node::of(value) {
+            Node::Null => StaticNode::Null,
+            Node::Bool(b) => StaticNode::Bool(b),
+            Node::I64(n) => StaticNode::I64(n),
+            Node::U64(n) => StaticNode::U64(n),
+            #[cfg(feature = "128bit")]
+            Node::I128(n) => StaticNode::I128(n),
+            #[cfg(feature = "128bit")]
+            Node::U128(n) => StaticNode::U128(n),
+            // without 128 bit support large integers are kept as floats
+            #[cfg(not(feature = "128bit"))]
+            #[allow(clippy::cast_precision_loss)]
+            Node::I128(n) => i64::try_from(n).map_or(StaticNode::F64(n as f64), StaticNode::I64),
+            #[cfg(not(feature = "128bit"))]
+            #[allow(clippy::cast_precision_loss)]
+            Node::U128(n) => u64::try_from(n).map_or(StaticNode::F64(n as f64), StaticNode::U64),
+            Node::F64(n) => StaticNode::F64(n),
+            Node::String(s) => return self.value(Entry::String(Cow::Borrowed(s))),
+            Node::Array(array) => {
+                self.start(false);
+                for v in array.iter() {
+                    self.copy(v);
+                }
+                return self.end();
+            }
+            Node::Object(object) => {
+                self.start(true);
+                for (k, v) in object.iter() {
+                    self.value(Entry::String(Cow::Borrowed(k.borrow())));
+                    self.copy(v);
+                }
+                return self.end();
+            }
+        };
+        self.value(Entry::Static(s));
+    }
+
+    /// Places the root as the last node and links the containers to their
+    /// children
+    fn finish(mut self) -> Tape<'input> {
+        // the tokenizer reports incomplete documents as errors
+        let root = self
+            .pending
+            .pop()
+            .unwrap_or((TapeValue(Entry::Static(StaticNode::Null)), 0));
+        Self::place(&mut self.nodes, &mut self.links, None, root);
+        let mut nodes = self.nodes;
+        // all pointers are derived from the same base pointer after the
+        // last push so they stay valid as long as the vector isn't modified
+        let base = nodes.as_mut_ptr();
+        for (idx, first) in self.links {
+            // SAFETY: both indices are nodes of the tape, `first` is the
+            // length of the tape for empty containers and never read
+            unsafe {
+                if let Entry::Array(children) | Entry::Object(children) = &mut (*base.add(idx)).0 {
+                    children.first = base.add(first);
+                }
+            }
+        }
+        Tape { nodes }
+    }
+}
+
+impl<'input> Tape<'input> {
+    /// Parses a JSON document into a tape, strings without escapes are
+    /// borrowed from the input
+    ///
+    /// # Errors
+    ///
+    /// if the input is not a valid JSON document
+    pub fn parse(input: &'input str) -> Result<Self, TokenizerError> {
+        let mut builder = Builder::default();
+        // reading past the root reports trailing data
+        for token in Tokenizer::new(input.as_bytes()) {
+            match token? {
+                Token::ObjectStart => builder.start(true),
+                Token::ArrayStart => builder.start(false),
+                Token::End => builder.end(),
+                Token::Key(s) | Token::String(s) => builder.value(Entry::String(s)),
+                Token::Static(s) => builder.value(Entry::Static(s)),
+            }
+        }
+        Ok(builder.finish())
+    }
+
+    /// Copies a value onto a tape, strings are borrowed from the value
+    #[must_use]
+    pub fn from_value<V>(value: &'input V) -> Self
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        let mut builder = Builder::default();
+        builder.copy(value);
+        builder.finish()
+    }
+
+    /// The root value of the tape
+    #[must_use]
+    pub fn as_value(&self) -> &TapeValue<'input> {
+        // a tape always holds at least its root, it is the last node
+        &self.nodes[self.nodes.len() - 1]
+    }
+
+    /// Number of nodes on the tape
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// A tape always contains at least its root node
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<'input> TapeValue<'input> {
+    /// The nodes of an array or the alternating keys and values of an object
+    fn children(&self) -> &[TapeValue<'input>] {
+        match &self.0 {
+            // SAFETY: the children were placed on the tape together with
+            // this node and live as long as it
+            Entry::Array(children) | Entry::Object(children) if children.len > 0 => unsafe {
+                std::slice::from_raw_parts(children.first, children.len)
+            },
+            _ => &[],
+        }
+    }
+
+    fn as_key(&self) -> &str {
+        self.as_str().unwrap_or_default()
+    }
+}
+
+impl<'input> Array for TapeArray<'input> {
+    type Element = TapeValue<'input>;
+    type Iter<'i>
+        = std::slice::Iter<'i, TapeValue<'input>>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<I>(&self, i: I) -> Option<&<I as std::slice::SliceIndex<[Self::Element]>>::Output>
+    where
+        I: std::slice::SliceIndex<[Self::Element]>,
+    {
+        self.0.children().get(i)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.children().iter()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.children().len()
+    }
+}
+
+impl<'input> Object for TapeObject<'input> {
+    type Key = str;
+    type Element = TapeValue<'input>;
+    type Iter<'i>
+        = std::iter::Map<
+        std::slice::ChunksExact<'i, TapeValue<'input>>,
+        fn(&'i [TapeValue<'input>]) -> (&'i str, &'i TapeValue<'input>),
+    >
+    where
+        Self: 'i;
+    type Keys<'i>
+        = std::iter::Map<
+        std::iter::StepBy<std::slice::Iter<'i, TapeValue<'input>>>,
+        fn(&'i TapeValue<'input>) -> &'i str,
+    >
+    where
+        Self: 'i;
+    type Values<'i>
+        = std::iter::StepBy<std::iter::Skip<std::slice::Iter<'i, TapeValue<'input>>>>
+    where
+        Self: 'i;
+
+    #[inline]
+    fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        self.0
+            .children()
+            .chunks_exact(2)
+            .find(|pair| pair[0].as_key().borrow() == k)
+            .map(|pair| &pair[1])
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0
+            .children()
+            .chunks_exact(2)
+            .map(|pair| (pair[0].as_key(), &pair[1]))
+    }
+
+    #[inline]
+    fn keys(&self) -> Self::Keys<'_> {
+        self.0.children().iter().step_by(2).map(TapeValue::as_key)
+    }
+
+    #[inline]
+    fn values(&self) -> Self::Values<'_> {
+        self.0.children().iter().skip(1).step_by(2)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.children().len() / 2
+    }
+}
+
+impl TypedValue for TapeValue<'_> {
+    #[inline]
+    fn value_type(&self) -> ValueType {
+        match &self.0 {
+            Entry::Static(s) => s.value_type(),
+            Entry::String(_) => ValueType::String,
+            Entry::Array(_) => ValueType::Array,
+            Entry::Object(_) => ValueType::Object,
+        }
+    }
+}
+
+impl ValueAsScalar for TapeValue<'_> {
+    #[inline]
+    fn as_null(&self) -> Option<()> {
+        self.as_static()?.as_null()
+    }
+
+    #[inline]
+    fn as_bool(&self) -> Option<bool> {
+        self.as_static()?.as_bool()
+    }
+
+    #[inline]
+    fn as_i128(&self) -> Option<i128> {
+        self.as_static()?.as_i128()
+    }
+
+    #[inline]
+    fn as_i64(&self) -> Option<i64> {
+        self.as_static()?.as_i64()
+    }
+
+    #[inline]
+    fn as_u128(&self) -> Option<u128> {
+        self.as_static()?.as_u128()
+    }
+
+    #[inline]
+    fn as_u64(&self) -> Option<u64> {
+        self.as_static()?.as_u64()
+    }
+
+    #[inline]
+    fn as_f64(&self) -> Option<f64> {
+        self.as_static()?.as_f64()
+    }
+
+    #[inline]
+    fn cast_f64(&self) -> Option<f64> {
+        self.as_static()?.cast_f64()
+    }
+
+    #[inline]
+    fn as_str(&self) -> Option<&str> {
+        if let Entry::String(s) = &self.0 {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl TapeValue<'_> {
+    fn as_static(&self) -> Option<&StaticNode> {
+        if let Entry::Static(s) = &self.0 {
+            Some(s)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'input> ValueAsContainer for TapeValue<'input> {
+    type Array = TapeArray<'input>;
+    type Object = TapeObject<'input>;
+
+    #[inline]
+    fn as_array(&self) -> Option<&Self::Array> {
+        if let Entry::Array(_) = self.0 {
+            // SAFETY: `TapeArray` is a transparent wrapper around `TapeValue`
+            Some(unsafe { &*(self as *const Self).cast::<TapeArray<'input>>() })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn as_object(&self) -> Option<&Self::Object> {
+        if let Entry::Object(_) = self.0 {
+            // SAFETY: `TapeObject` is a transparent wrapper around `TapeValue`
+            Some(unsafe { &*(self as *const Self).cast::<TapeObject<'input>>() })
+        } else {
+            None
+        }
+    }
+}
+
+impl Writable for TapeValue<'_> {
+    fn encode(&self) -> String {
+        let mut g = DumpGenerator::new();
+        // writing to a String can not fail
+        let _: io::Result<()> = g.write_json(self);
+        g.consume()
+    }
+
+    fn encode_pp(&self) -> String {
+        let mut g = PrettyGenerator::new(2);
+        let _: io::Result<()> = g.write_json(self);
+        g.consume()
+    }
+
+    fn write<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        WriterGenerator::new(w).write_json(self)
+    }
+
+    fn write_pp<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        PrettyWriterGenerator::new(w, 2).write_json(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tape;
+    use crate::generator::encoded_len;
+    use crate::parser::ParseErrorKind;
+    use crate::prelude::*;
+    use crate::tokenizer::TokenizerError;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    const DOC: &str = r#"{"a":[1,-2,3.5,true,null],"b":{"c":"d\n","e":{}},"f":[]}"#;
+
+    #[test]
+    fn round_trip() {
+        let tape = Tape::parse(DOC).expect("valid");
+        let v = tape.as_value();
+        assert_eq!(v.encode(), DOC);
+        assert_eq!(encoded_len(v), DOC.len());
+        // the root, its 3 keys and values, 5 elements, 2 keys and values
+        assert_eq!(tape.len(), 1 + 6 + 5 + 4);
+        assert_eq!(v.get("a").and_then(|a| a.get_idx(2)?.as_f64()), Some(3.5));
+        assert_eq!(v.get("b").and_then(|b| b.get_str("c")), Some("d\n"));
+        assert_eq!(
+            v.get("f")
+                .and_then(ValueAsContainer::as_array)
+                .map(ArrayTrait::len),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let tape = Tape::parse(r#"{"a":1,"b":2,"a":{"x":[3]}}"#).expect("valid");
+        assert_eq!(tape.as_value().encode(), r#"{"a":{"x":[3]},"b":2}"#);
+        let tape = Tape::parse(r#"[{"a":[1,{"b":2}],"a":[3],"c":{"a":1,"a":[]}},{"a":0}]"#)
+            .expect("valid");
+        assert_eq!(
+            tape.as_value().encode(),
+            r#"[{"a":[3],"c":{"a":[]}},{"a":0}]"#
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(Tape::parse(r#"{"a":1"#).is_err());
+        assert!(Tape::parse("[1,]").is_err());
+        assert!(Tape::parse("").is_err());
+        for input in ["[1]]", "1 2", "[1] x", "{} {}"] {
+            let e = Tape::parse(input).err();
+            assert!(
+                matches!(
+                    e,
+                    Some(TokenizerError::Syntax(_, ParseErrorKind::TrailingData))
+                ),
+                "{input}: {e:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_value() {
+        let native = NativeValue::from(vec![
+            NativeValue::from("s"),
+            NativeValue::from(vec![1_u8, 2]),
+            NativeValue::from(HashMap::from([("k".to_string(), vec![true])])),
+            NativeValue::from(Vec::<u8>::new()),
+        ]);
+        let tape = Tape::from_value(&native);
+        assert_eq!(tape.as_value().encode(), native.encode());
+    }
+
+    #[test]
+    fn survives_moves() {
+        let tape = Tape::parse(DOC).expect("valid");
+        let boxed = Box::new(tape);
+        // growing the outer vector moves the tapes around
+        let mut tapes = vec![*boxed];
+        for _ in 0..64 {
+            tapes.push(Tape::parse(DOC).expect("valid"));
+        }
+        let tape = tapes.swap_remove(0);
+        assert_eq!(tape.as_value().encode(), DOC);
+
+        let moved = std::thread::spawn(move || {
+            assert_eq!(tape.as_value().encode(), DOC);
+            tape
+        })
+        .join()
+        .expect("no panic");
+        let shared = Arc::new(moved);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || shared.as_value().encode())
+            })
+            .collect();
+        for h in handles {
+            assert_eq!(h.join().expect("no panic"), DOC);
+        }
+    }
+}
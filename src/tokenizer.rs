@@ -0,0 +1,672 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::parser::{number, number_len, unescape, ParseErrorKind};
+use crate::StaticNode;
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// A token of a JSON document
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'input> {
+    /// The start of an object, followed by `Key` / value pairs and an `End`
+    ObjectStart,
+    /// The key of the value that follows
+    Key(Cow<'input, str>),
+    /// The start of an array, followed by values and an `End`
+    ArrayStart,
+    /// The end of the innermost object or array
+    End,
+    /// A string value
+    String(Cow<'input, str>),
+    /// A scalar value
+    Static(StaticNode),
+}
+
+/// An error encountered while tokenizing JSON
+#[derive(Debug)]
+pub enum TokenizerError {
+    /// The input is not valid JSON at the given byte offset
+    Syntax(usize, ParseErrorKind),
+    /// A string starting at the given byte offset is not valid UTF-8
+    InvalidUtf8(usize),
+    /// Reading the input failed
+    Io(io::Error),
+}
+
+impl fmt::Display for TokenizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(offset, kind) => write!(f, "{kind} at byte {offset}"),
+            Self::InvalidUtf8(offset) => write!(f, "Invalid UTF-8 in string at byte {offset}"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for TokenizerError {}
+
+impl From<io::Error> for TokenizerError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// A value has to follow
+    Value,
+    /// Right after `{`
+    ObjectFirst,
+    /// After a value inside an object
+    ObjectNext,
+    /// Right after `[`
+    ArrayFirst,
+    /// After a value inside an array
+    ArrayNext,
+    /// The document is complete
+    Done,
+    /// An error was returned, no more tokens follow
+    Failed,
+}
+
+enum Source<'input, R> {
+    Slice(&'input [u8]),
+    Reader {
+        reader: R,
+        buffer: Vec<u8>,
+        eof: bool,
+    },
+}
+
+/// A pull tokenizer for JSON documents, it reads one token at a time
+/// without building a tree. Strings are borrowed from slices when they
+/// contain no escapes.
+pub struct Tokenizer<'input, R> {
+    source: Source<'input, R>,
+    /// position in the slice or buffer
+    pos: usize,
+    /// bytes dropped from the front of the buffer
+    base: usize,
+    state: State,
+    /// `true` for objects, `false` for arrays
+    stack: Vec<bool>,
+}
+
+impl<'input> Tokenizer<'input, io::Empty> {
+    /// Creates a tokenizer over a byte slice
+    #[must_use]
+    pub fn new(input: &'input [u8]) -> Self {
+        Self {
+            source: Source::Slice(input),
+            pos: 0,
+            base: 0,
+            state: State::Value,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<R: Read> Tokenizer<'static, R> {
+    /// Creates a tokenizer that reads its input incrementally
+    #[must_use]
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            source: Source::Reader {
+                reader,
+                buffer: Vec::with_capacity(CHUNK_SIZE),
+                eof: false,
+            },
+            pos: 0,
+            base: 0,
+            state: State::Value,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<'input, R: Read> Tokenizer<'input, R> {
+    /// The byte offset in the input
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.base + self.pos
+    }
+
+    /// The number of objects and arrays the tokenizer is currently in
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Skips the next value including everything nested in it, use it after
+    /// a `Key` to ignore the field. If the enclosing object or array ends
+    /// instead its `End` is consumed.
+    ///
+    /// # Errors
+    ///
+    /// if the input is not valid JSON or can not be read
+    pub fn skip_value(&mut self) -> Result<(), TokenizerError> {
+        let mut depth = 0_usize;
+        loop {
+            let token = self
+                .next_token()?
+                .ok_or_else(|| self.syntax(ParseErrorKind::Eof))?;
+            match token {
+                Token::ObjectStart | Token::ArrayStart => depth += 1,
+                Token::End => depth = depth.saturating_sub(1),
+                Token::Key(_) => continue,
+                Token::String(_) | Token::Static(_) => (),
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        match &self.source {
+            Source::Slice(s) => s,
+            Source::Reader { buffer, .. } => buffer,
+        }
+    }
+
+    fn available(&self) -> &[u8] {
+        self.data().get(self.pos..).unwrap_or_default()
+    }
+
+    fn syntax(&self, kind: ParseErrorKind) -> TokenizerError {
+        TokenizerError::Syntax(self.offset(), kind)
+    }
+
+    /// Makes sure at least `n` bytes are available unless the input ends,
+    /// this can move the buffer so positions are only valid until the next
+    /// call
+    fn fill(&mut self, n: usize) -> io::Result<()> {
+        if let Source::Reader {
+            reader,
+            buffer,
+            eof,
+        } = &mut self.source
+        {
+            if buffer.len() - self.pos >= n || *eof {
+                return Ok(());
+            }
+            buffer.drain(..self.pos);
+            self.base += self.pos;
+            self.pos = 0;
+            while buffer.len() < n && !*eof {
+                let len = buffer.len();
+                buffer.resize(len + CHUNK_SIZE.max(n - len), 0);
+                match reader.read(&mut buffer[len..]) {
+                    Ok(read) => {
+                        buffer.truncate(len + read);
+                        *eof = read == 0;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => buffer.truncate(len),
+                    Err(e) => {
+                        buffer.truncate(len);
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the next non whitespace byte without consuming it
+    fn peek(&mut self) -> Result<Option<u8>, TokenizerError> {
+        loop {
+            self.fill(1)?;
+            let available = self.available();
+            if available.is_empty() {
+                return Ok(None);
+            }
+            let ws = available
+                .iter()
+                .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+                .count();
+            let rest = available.len() - ws;
+            self.pos += ws;
+            if rest > 0 {
+                return Ok(self.available().first().copied());
+            }
+        }
+    }
+
+    fn unexpected(&mut self) -> TokenizerError {
+        // report a full character if the input is valid UTF-8 here
+        if let Err(e) = self.fill(4) {
+            return e.into();
+        }
+        let available = self.available();
+        if available.is_empty() {
+            return self.syntax(ParseErrorKind::Eof);
+        }
+        let len = available.len().min(4);
+        let c = (1..=len)
+            .find_map(|n| std::str::from_utf8(&available[..n]).ok())
+            .and_then(|s| s.chars().next())
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        self.syntax(ParseErrorKind::UnexpectedCharacter(c))
+    }
+
+    fn after_value(&mut self) {
+        self.state = match self.stack.last() {
+            None => State::Done,
+            Some(true) => State::ObjectNext,
+            Some(false) => State::ArrayNext,
+        };
+    }
+
+    fn end(&mut self) -> Token<'input> {
+        self.pos += 1;
+        self.stack.pop();
+        self.after_value();
+        Token::End
+    }
+
+    /// Reads a key including the following `:`
+    fn key(&mut self) -> Result<Token<'input>, TokenizerError> {
+        if self.peek()? != Some(b'"') {
+            return Err(self.unexpected());
+        }
+        let key = self.string()?;
+        match self.peek()? {
+            Some(b':') => self.pos += 1,
+            Some(_) => return Err(self.unexpected()),
+            None => return Err(self.syntax(ParseErrorKind::Eof)),
+        }
+        self.state = State::Value;
+        Ok(Token::Key(key))
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'input>>, TokenizerError> {
+        // don't touch the input again after an error
+        if self.state == State::Failed {
+            return Ok(None);
+        }
+        loop {
+            let next = self.peek()?;
+            let b = match next {
+                Some(b) => b,
+                None if matches!(self.state, State::Done | State::Failed) => return Ok(None),
+                None => return Err(self.syntax(ParseErrorKind::Eof)),
+            };
+            match (self.state, b) {
+                (State::Failed, _) => return Ok(None),
+                (State::Done, _) => return Err(self.syntax(ParseErrorKind::TrailingData)),
+                (State::ObjectFirst | State::ObjectNext, b'}')
+                | (State::ArrayFirst | State::ArrayNext, b']') => {
+                    return Ok(Some(self.end()));
+                }
+                (State::ObjectFirst, _) => return self.key().map(Some),
+                (State::ObjectNext, b',') => {
+                    self.pos += 1;
+                    return self.key().map(Some);
+                }
+                (State::ArrayFirst, _) => self.state = State::Value,
+                (State::ArrayNext, b',') => {
+                    self.pos += 1;
+                    self.state = State::Value;
+                }
+                (State::ObjectNext | State::ArrayNext, _) => return Err(self.unexpected()),
+                (State::Value, _) => return self.value(b).map(Some),
+            }
+        }
+    }
+
+    fn value(&mut self, b: u8) -> Result<Token<'input>, TokenizerError> {
+        let token = match b {
+            b'{' => {
+                self.pos += 1;
+                self.stack.push(true);
+                self.state = State::ObjectFirst;
+                return Ok(Token::ObjectStart);
+            }
+            b'[' => {
+                self.pos += 1;
+                self.stack.push(false);
+                self.state = State::ArrayFirst;
+                return Ok(Token::ArrayStart);
+            }
+            b'"' => Token::String(self.string()?),
+            b't' => self.literal(b"true", StaticNode::Bool(true))?,
+            b'f' => self.literal(b"false", StaticNode::Bool(false))?,
+            b'n' => self.literal(b"null", StaticNode::Null)?,
+            b'-' | b'0'..=b'9' => self.number()?,
+            _ => return Err(self.unexpected()),
+        };
+        self.after_value();
+        Ok(token)
+    }
+
+    fn literal(
+        &mut self,
+        literal: &[u8],
+        node: StaticNode,
+    ) -> Result<Token<'input>, TokenizerError> {
+        self.fill(literal.len())?;
+        for b in literal {
+            if self.available().first() != Some(b) {
+                return Err(self.unexpected());
+            }
+            self.pos += 1;
+        }
+        Ok(Token::Static(node))
+    }
+
+    fn number(&mut self) -> Result<Token<'input>, TokenizerError> {
+        let is_number = |b: &u8| matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E');
+        // numbers are short so we make sure they are buffered as a whole
+        let mut len = 0;
+        loop {
+            let available = self.available();
+            let candidate = available.iter().take_while(|b| is_number(b)).count();
+            if candidate < available.len() || len == candidate {
+                len = candidate;
+                break;
+            }
+            len = candidate;
+            self.fill(len + 1)?;
+        }
+        let available = &self.available()[..len];
+        let end = number_len(available).map_err(|i| {
+            let kind = if i == len && self.available().len() == len {
+                ParseErrorKind::Eof
+            } else {
+                ParseErrorKind::InvalidNumber
+            };
+            TokenizerError::Syntax(self.offset() + i, kind)
+        })?;
+        let node = std::str::from_utf8(&available[..end])
+            .ok()
+            .and_then(number)
+            .ok_or_else(|| self.syntax(ParseErrorKind::InvalidNumber))?;
+        self.pos += end;
+        Ok(Token::Static(node))
+    }
+
+    /// Reads a string starting at the opening quote, strings from slices
+    /// without escapes are borrowed
+    fn string(&mut self) -> Result<Cow<'input, str>, TokenizerError> {
+        let start = self.offset();
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut escaped = false;
+        loop {
+            self.fill(1)?;
+            let available = self.available();
+            let plain = available
+                .iter()
+                .position(|b| matches!(b, b'"' | b'\\' | 0..=0x1f));
+            let Some(plain) = plain else {
+                if available.is_empty() {
+                    return Err(self.syntax(ParseErrorKind::Eof));
+                }
+                let len = available.len();
+                out.extend_from_slice(available);
+                self.pos += len;
+                continue;
+            };
+            out.extend_from_slice(&available[..plain]);
+            self.pos += plain;
+            match self.available()[0] {
+                b'"' => {
+                    self.pos += 1;
+                    break;
+                }
+                b'\\' => {
+                    escaped = true;
+                    // the longest escape is a surrogate pair
+                    self.fill(12)?;
+                    let (c, len) = unescape(&self.available()[1..])
+                        .map_err(|(kind, i)| TokenizerError::Syntax(self.offset() + 1 + i, kind))?;
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    self.pos += 1 + len;
+                }
+                _ => return Err(self.syntax(ParseErrorKind::ControlCharacter)),
+            }
+        }
+        if let (Source::Slice(input), false) = (&self.source, escaped) {
+            let input: &'input [u8] = input;
+            return std::str::from_utf8(&input[start + 1..self.pos - 1])
+                .map(Cow::Borrowed)
+                .map_err(|_| TokenizerError::InvalidUtf8(start));
+        }
+        String::from_utf8(out)
+            .map(Cow::Owned)
+            .map_err(|_| TokenizerError::InvalidUtf8(start))
+    }
+}
+
+impl<'input, R: Read> Iterator for Tokenizer<'input, R> {
+    type Item = Result<Token<'input>, TokenizerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_token() {
+            Ok(token) => token.map(Ok),
+            Err(e) => {
+                self.state = State::Failed;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Token, Tokenizer, TokenizerError};
+    use crate::parser::ParseErrorKind;
+    use crate::StaticNode;
+    use std::borrow::Cow;
+    use std::io::{self, Read};
+
+    /// Reader handing out a single byte per call, interrupted every other
+    /// call
+    struct Trickle<'a> {
+        data: &'a [u8],
+        interrupt: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            match (self.data.split_first(), buf.first_mut()) {
+                (Some((b, rest)), Some(out)) => {
+                    *out = *b;
+                    self.data = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn trickle(data: &[u8]) -> Tokenizer<'static, Trickle<'_>> {
+        Tokenizer::from_reader(Trickle {
+            data,
+            interrupt: false,
+        })
+    }
+
+    fn tokens(input: &[u8]) -> Vec<Token<'_>> {
+        let from_slice: Vec<Token<'_>> = Tokenizer::new(input)
+            .collect::<Result<_, _>>()
+            .expect("valid JSON");
+        let from_reader: Vec<Token<'_>> = trickle(input)
+            .collect::<Result<_, _>>()
+            .expect("valid JSON");
+        assert_eq!(from_slice, from_reader);
+        from_slice
+    }
+
+    fn syntax(input: &[u8]) -> (usize, ParseErrorKind) {
+        let first_error =
+            |mut t: Box<dyn Iterator<Item = Result<Token<'_>, TokenizerError>> + '_>| {
+                let e = t.find_map(Result::err);
+                // no more tokens after an error
+                assert!(t.next().is_none());
+                match e {
+                    Some(TokenizerError::Syntax(offset, kind)) => (offset, kind),
+                    other => panic!("unexpected {other:?}"),
+                }
+            };
+        let from_slice = first_error(Box::new(Tokenizer::new(input)));
+        let from_reader = first_error(Box::new(trickle(input)));
+        assert_eq!(from_slice, from_reader);
+        from_slice
+    }
+
+    #[test]
+    fn document() {
+        let input = br#" {"a": [1, -2.5e1, "x\n", true, null, {}], "b\u0063": ""} "#;
+        assert_eq!(
+            tokens(input),
+            vec![
+                Token::ObjectStart,
+                Token::Key("a".into()),
+                Token::ArrayStart,
+                Token::Static(StaticNode::I64(1)),
+                Token::Static(StaticNode::F64(-25.0)),
+                Token::String("x\n".into()),
+                Token::Static(StaticNode::Bool(true)),
+                Token::Static(StaticNode::Null),
+                Token::ObjectStart,
+                Token::End,
+                Token::End,
+                Token::Key("bc".into()),
+                Token::String("".into()),
+                Token::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn borrowed_strings() {
+        let mut t = Tokenizer::new(br#"["plain","esc\"aped"]"#);
+        assert_eq!(t.next().map(Result::ok), Some(Some(Token::ArrayStart)));
+        assert!(matches!(
+            t.next(),
+            Some(Ok(Token::String(Cow::Borrowed("plain"))))
+        ));
+        assert!(matches!(
+            t.next(),
+            Some(Ok(Token::String(Cow::Owned(s)))) if s == "esc\"aped"
+        ));
+    }
+
+    #[test]
+    fn long_input() {
+        // the surrogate pair escape straddles the first 8 KiB reader chunk
+        let s = format!("{}\\ud83d\\ude00", "y".repeat(8_185));
+        let input = format!("[\"{s}\", 123456789, \"{s}\"]");
+        let t: Vec<Token<'_>> = Tokenizer::from_reader(input.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("valid JSON");
+        assert_eq!(
+            Tokenizer::new(input.as_bytes())
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+                .as_ref(),
+            Some(&t)
+        );
+        let expected = format!("{}😀", "y".repeat(8_185));
+        assert_eq!(t[1], Token::String(expected.into()));
+        assert_eq!(t[2], Token::Static(StaticNode::I64(123_456_789)));
+    }
+
+    #[test]
+    fn skip_value() {
+        let mut t = Tokenizer::new(br#"{"skip": {"a": [1, {"b": 2}]}, "keep": 3}"#);
+        assert!(matches!(t.next(), Some(Ok(Token::ObjectStart))));
+        assert!(matches!(t.next(), Some(Ok(Token::Key(_)))));
+        t.skip_value().expect("valid value");
+        assert_eq!(t.depth(), 1);
+        assert_eq!(
+            t.next().map(Result::ok),
+            Some(Some(Token::Key("keep".into())))
+        );
+        assert_eq!(
+            t.next().map(Result::ok),
+            Some(Some(Token::Static(StaticNode::I64(3))))
+        );
+        assert!(matches!(t.next(), Some(Ok(Token::End))));
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn malformed() {
+        for (input, offset, kind) in [
+            (&b""[..], 0, ParseErrorKind::Eof),
+            (b"[1,]", 3, ParseErrorKind::UnexpectedCharacter(']')),
+            (b"[1 2]", 3, ParseErrorKind::UnexpectedCharacter('2')),
+            (b"{\"a\" 1}", 5, ParseErrorKind::UnexpectedCharacter('1')),
+            (b"{1:1}", 1, ParseErrorKind::UnexpectedCharacter('1')),
+            (b"{\"a\":1", 6, ParseErrorKind::Eof),
+            (b"1 2", 2, ParseErrorKind::TrailingData),
+            (b"1.", 2, ParseErrorKind::Eof),
+            (b"1.x", 2, ParseErrorKind::InvalidNumber),
+            (b"1e400", 0, ParseErrorKind::InvalidNumber),
+            (b"tru", 3, ParseErrorKind::Eof),
+            (
+                b"[\xff]",
+                1,
+                ParseErrorKind::UnexpectedCharacter('\u{fffd}'),
+            ),
+            (b"[nul!]", 4, ParseErrorKind::UnexpectedCharacter('!')),
+            (b"\"abc", 4, ParseErrorKind::Eof),
+            (b"\"\\x\"", 2, ParseErrorKind::InvalidEscape),
+            (b"\"\\u12\"", 5, ParseErrorKind::InvalidUnicode),
+            (b"\"a\nb\"", 2, ParseErrorKind::ControlCharacter),
+            (
+                "[☃]".as_bytes(),
+                1,
+                ParseErrorKind::UnexpectedCharacter('☃'),
+            ),
+        ] {
+            assert_eq!(
+                syntax(input),
+                (offset, kind),
+                "{:?}",
+                String::from_utf8_lossy(input)
+            );
+        }
+        let mut t = Tokenizer::new(b"[\"\xff\"]");
+        assert!(matches!(
+            t.nth(1),
+            Some(Err(TokenizerError::InvalidUtf8(1)))
+        ));
+    }
+
+    #[test]
+    fn deep_nesting() {
+        // the tokenizer keeps its own stack, deep input can't overflow it
+        let input = "[".repeat(1_000_000);
+        let mut t = Tokenizer::new(input.as_bytes());
+        assert_eq!(t.by_ref().take(1_000_000).flatten().count(), 1_000_000);
+        assert_eq!(t.depth(), 1_000_000);
+        assert!(matches!(
+            t.next(),
+            Some(Err(TokenizerError::Syntax(1_000_000, ParseErrorKind::Eof)))
+        ));
+    }
+
+    #[test]
+    fn io_errors() {
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        let mut t = Tokenizer::from_reader(Broken);
+        assert!(
+            matches!(t.next(), Some(Err(TokenizerError::Io(e))) if e.kind() == io::ErrorKind::BrokenPipe)
+        );
+        assert!(t.next().is_none());
+    }
+}
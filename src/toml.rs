@@ -0,0 +1,519 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::{
+    array::Array,
+    base::{TypedValue, ValueAsContainer, ValueAsScalar},
+    generator::{BaseGenerator, Node},
+    object::Object,
+    ValueType,
+};
+
+/// The TOML version to produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TomlVersion {
+    /// TOML 0.5, arrays have to be homogeneous
+    V0_5,
+    /// TOML 1.0
+    #[default]
+    V1_0,
+}
+
+/// Reasons a value can not be written as TOML
+#[derive(Debug)]
+pub enum TomlError {
+    /// The top level value is not an object
+    NotATable(ValueType),
+    /// TOML has no representation for null, holds the key path of the value
+    Null(String),
+    /// An array has elements of different types which is not allowed
+    /// before TOML 1.0, holds the key path of the array
+    MixedArray(String),
+    /// An integer does not fit into TOML's 64 bit signed integers, holds
+    /// the key path of the value
+    OutOfRange(String),
+    /// An IO error was encountered
+    Io(io::Error),
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotATable(ty) => write!(f, "A TOML document has to be a table, got {ty}"),
+            Self::Null(path) => write!(f, "TOML can not represent the null value at `{path}`"),
+            Self::MixedArray(path) => write!(
+                f,
+                "The array at `{path}` has mixed types which this TOML version does not allow"
+            ),
+            Self::OutOfRange(path) => {
+                write!(
+                    f,
+                    "The integer at `{path}` does not fit into a TOML integer"
+                )
+            }
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+impl From<io::Error> for TomlError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// The kinds of values TOML distinguishes when checking arrays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    String,
+    Integer,
+    Float,
+    Bool,
+    Array,
+    Table,
+    Null,
+}
+
+impl<V> From<Node<'_, V>> for Kind
+where
+    V: ValueAsContainer,
+{
+    fn from(node: Node<'_, V>) -> Self {
+        match node {
+            Node::Null => Kind::Null,
+            Node::Bool(_) => Kind::Bool,
+            Node::I64(_) | Node::U64(_) | Node::I128(_) | Node::U128(_) => Kind::Integer,
+            Node::F64(_) => Kind::Float,
+            Node::String(_) => Kind::String,
+            Node::Array(_) => Kind::Array,
+            Node::Object(_) => Kind::Table,
+        }
+    }
+}
+
+/// Checks if a value can be written as TOML
+///
+/// # Errors
+///
+/// if the value can not be represented in the given TOML version
+pub fn validate<V>(value: &V, version: TomlVersion) -> Result<(), TomlError>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    match Node::of(value) {
+        Node::Object(_) => validate_value(value, version, &mut Vec::new()),
+        _ => Err(TomlError::NotATable(value.value_type())),
+    }
+}
+
+fn validate_value<'v, V>(
+    value: &'v V,
+    version: TomlVersion,
+    path: &mut Vec<&'v str>,
+) -> Result<(), TomlError>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    let fits = match Node::of(value) {
+        Node::Null => return Err(TomlError::Null(path.join("."))),
+        Node::U64(n) => i64::try_from(n).is_ok(),
+        Node::I128(n) => i64::try_from(n).is_ok(),
+        Node::U128(n) => i64::try_from(n).is_ok(),
+        Node::Array(array) => {
+            if version == TomlVersion::V0_5 {
+                let mut kinds = array.iter().map(|v| Kind::from(Node::of(v)));
+                if let Some(first) = kinds.next() {
+                    if kinds.any(|k| k != first) {
+                        return Err(TomlError::MixedArray(path.join(".")));
+                    }
+                }
+            }
+            for item in array.iter() {
+                validate_value(item, version, path)?;
+            }
+            true
+        }
+        Node::Object(object) => {
+            for (k, v) in object.iter() {
+                path.push(k.borrow());
+                validate_value(v, version, path)?;
+                path.pop();
+            }
+            true
+        }
+        _ => true,
+    };
+    if fits {
+        Ok(())
+    } else {
+        Err(TomlError::OutOfRange(path.join(".")))
+    }
+}
+
+/// TOML generator writing to a Writer. Nested objects are written as
+/// `[tables]`, arrays of objects as `[[arrays of tables]]` and inline
+/// tables are used where neither is possible.
+pub struct TomlGenerator<'w, W>
+where
+    W: 'w + Write,
+{
+    writer: &'w mut W,
+    version: TomlVersion,
+    written: bool,
+}
+
+impl<'w, W> TomlGenerator<'w, W>
+where
+    W: 'w + Write,
+{
+    /// Creates a new generator producing TOML 1.0
+    pub fn new(writer: &'w mut W) -> Self {
+        Self::with_version(writer, TomlVersion::default())
+    }
+
+    /// Creates a new generator producing the given TOML version
+    pub fn with_version(writer: &'w mut W, version: TomlVersion) -> Self {
+        TomlGenerator {
+            writer,
+            version,
+            written: false,
+        }
+    }
+
+    /// Writes a value as a TOML document, the value is validated first so
+    /// nothing is written if it can not be represented.
+    ///
+    /// # Errors
+    ///
+    /// if the value can not be represented or an IO error is encountered
+    pub fn write_value<V>(&mut self, value: &V) -> Result<(), TomlError>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        validate(value, self.version)?;
+        if let Node::Object(object) = Node::of(value) {
+            self.write_table::<V>(object, &mut Vec::new())?;
+        }
+        Ok(())
+    }
+
+    fn write_table<'v, V>(
+        &mut self,
+        object: &'v V::Object,
+        path: &mut Vec<&'v str>,
+    ) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer + 'v,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        // All key/value pairs have to come before the first sub table
+        for (k, v) in object.iter() {
+            match Node::of(v) {
+                Node::Object(object) if !object.is_empty() => (),
+                Node::Array(array) if is_table_array(array) => (),
+                node => {
+                    self.write_key(k.borrow())?;
+                    self.write(b" = ")?;
+                    self.write_inline(node)?;
+                    self.write_char(b'\n')?;
+                    self.written = true;
+                }
+            }
+        }
+        for (k, v) in object.iter() {
+            match Node::of(v) {
+                Node::Object(object) if !object.is_empty() => {
+                    path.push(k.borrow());
+                    self.write_header(path, false)?;
+                    self.write_table::<V>(object, path)?;
+                    path.pop();
+                }
+                Node::Array(array) if is_table_array(array) => {
+                    path.push(k.borrow());
+                    for item in array.iter() {
+                        self.write_header(path, true)?;
+                        if let Some(object) = item.as_object() {
+                            self.write_table::<V>(object, path)?;
+                        }
+                    }
+                    path.pop();
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_header(&mut self, path: &[&str], array: bool) -> io::Result<()> {
+        if self.written {
+            self.write_char(b'\n')?;
+        }
+        self.write(if array { b"[[" } else { b"[" })?;
+        for (i, key) in path.iter().enumerate() {
+            if i > 0 {
+                self.write_char(b'.')?;
+            }
+            self.write_key(key)?;
+        }
+        self.write(if array { b"]]\n" } else { b"]\n" })?;
+        self.written = true;
+        Ok(())
+    }
+
+    fn write_key(&mut self, key: &str) -> io::Result<()> {
+        let bare = !key.is_empty()
+            && key
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+        if bare {
+            self.write(key.as_bytes())
+        } else {
+            self.write_basic_string(key)
+        }
+    }
+
+    fn write_inline<V>(&mut self, node: Node<V>) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        match node {
+            Node::Null => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "TOML can not represent null",
+            )),
+            Node::Bool(true) => self.write(b"true"),
+            Node::Bool(false) => self.write(b"false"),
+            Node::I64(n) => self.write_int(n),
+            Node::U64(n) => self.write_int(n),
+            Node::I128(n) => self.write_int(n),
+            Node::U128(n) => self.write_int(n),
+            Node::F64(n) => self.write_float(n),
+            Node::String(s) => self.write_basic_string(s),
+            Node::Array(array) => {
+                self.write_char(b'[')?;
+                for (i, item) in array.iter().enumerate() {
+                    if i > 0 {
+                        self.write(b", ")?;
+                    }
+                    self.write_inline(Node::of(item))?;
+                }
+                self.write_char(b']')
+            }
+            Node::Object(object) if object.is_empty() => self.write(b"{}"),
+            Node::Object(object) => {
+                self.write_char(b'{')?;
+                for (i, (k, v)) in object.iter().enumerate() {
+                    if i > 0 {
+                        self.write_char(b',')?;
+                    }
+                    self.write_char(b' ')?;
+                    self.write_key(k.borrow())?;
+                    self.write(b" = ")?;
+                    self.write_inline(Node::of(v))?;
+                }
+                self.write(b" }")
+            }
+        }
+    }
+
+    /// TOML basic strings use the same escapes as JSON with the exception
+    /// that `DEL` has to be escaped as well.
+    fn write_basic_string(&mut self, s: &str) -> io::Result<()> {
+        if !s.contains('\u{7f}') {
+            return self.write_string(s);
+        }
+        self.write_char(b'"')?;
+        for (i, part) in s.split('\u{7f}').enumerate() {
+            if i > 0 {
+                self.write(b"\\u007f")?;
+            }
+            self.write_string_content(part)?;
+        }
+        self.write_char(b'"')
+    }
+}
+
+/// Arrays that only contain objects are written as arrays of tables
+fn is_table_array<A, V>(array: &A) -> bool
+where
+    A: Array<Element = V>,
+    V: ValueAsContainer,
+{
+    !array.is_empty() && array.iter().all(|v| v.as_object().is_some())
+}
+
+impl<'w, W> BaseGenerator for TomlGenerator<'w, W>
+where
+    W: 'w + Write,
+{
+    type T = W;
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut W {
+        self.writer
+    }
+
+    #[inline]
+    fn write_min(&mut self, _: &[u8], min: u8) -> io::Result<()> {
+        self.writer.write_all(&[min])
+    }
+
+    fn write_float(&mut self, num: f64) -> io::Result<()> {
+        if num.is_nan() {
+            self.write(b"nan")
+        } else if num.is_infinite() {
+            self.write(if num > 0.0 { b"inf" } else { b"-inf" })
+        } else {
+            let mut buffer = ryu::Buffer::new();
+            self.write(buffer.format_finite(num).as_bytes())
+        }
+    }
+}
+
+/// Encodes a value as a TOML 1.0 document
+///
+/// # Errors
+///
+/// if the value can not be represented in TOML
+pub fn to_string<V>(value: &V) -> Result<String, TomlError>
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    let mut out = Vec::new();
+    TomlGenerator::new(&mut out).write_value(value)?;
+    // Original strings were unicode, numbers and escapes are all ASCII,
+    // therefore this is safe.
+    Ok(unsafe { String::from_utf8_unchecked(out) })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_string, TomlError, TomlGenerator, TomlVersion};
+    use crate::prelude::*;
+    use crate::{NativeValue, ValueType};
+    use std::collections::HashMap;
+
+    fn object(k: &str, v: NativeValue) -> NativeValue {
+        let mut map = HashMap::new();
+        map.insert(k.to_string(), v);
+        NativeValue::from(map)
+    }
+
+    fn toml(value: &NativeValue) -> String {
+        to_string(value).expect("valid TOML")
+    }
+
+    #[test]
+    fn key_values() {
+        assert_eq!(toml(&object("a", NativeValue::from(1_u64))), "a = 1\n");
+        assert_eq!(
+            toml(&object("a b", NativeValue::from("x\u{7f}\"y"))),
+            "\"a b\" = \"x\\u007f\\\"y\"\n"
+        );
+        assert_eq!(
+            toml(&object(
+                "f",
+                NativeValue::from(vec![f64::NAN, f64::NEG_INFINITY, 0.5])
+            )),
+            "f = [nan, -inf, 0.5]\n"
+        );
+        assert_eq!(
+            toml(&object(
+                "e",
+                NativeValue::from(HashMap::<String, NativeValue>::new())
+            )),
+            "e = {}\n"
+        );
+        assert_eq!(
+            toml(&NativeValue::from(HashMap::<String, NativeValue>::new())),
+            ""
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let value = object("srv", object("db", object("host", NativeValue::from("x"))));
+        assert_eq!(toml(&value), "[srv]\n\n[srv.db]\nhost = \"x\"\n");
+
+        let value = object(
+            "p",
+            NativeValue::from(vec![
+                object("n", NativeValue::from(1_u64)),
+                object("n", NativeValue::from(2_u64)),
+            ]),
+        );
+        assert_eq!(toml(&value), "[[p]]\nn = 1\n\n[[p]]\nn = 2\n");
+
+        // objects nested in arrays that are not arrays of tables are inline
+        let value = object(
+            "a",
+            NativeValue::from(vec![NativeValue::from(vec![object(
+                "x",
+                NativeValue::from(1_u64),
+            )])]),
+        );
+        assert_eq!(toml(&value), "a = [[{ x = 1 }]]\n");
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            to_string(&NativeValue::from(1_u64)),
+            Err(TomlError::NotATable(ValueType::U64))
+        ));
+        let value = object("a", object("b", NativeValue::null()));
+        match to_string(&value) {
+            Err(e @ TomlError::Null(_)) => {
+                assert_eq!(
+                    e.to_string(),
+                    "TOML can not represent the null value at `a.b`"
+                );
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        let value = object("n", NativeValue::from(vec![u64::MAX]));
+        assert!(matches!(to_string(&value), Err(TomlError::OutOfRange(p)) if p == "n"));
+    }
+
+    #[test]
+    fn mixed_arrays() {
+        let value = object(
+            "a",
+            NativeValue::from(vec![NativeValue::from(1_u64), NativeValue::from("x")]),
+        );
+        assert_eq!(toml(&value), "a = [1, \"x\"]\n");
+
+        let mut out = Vec::new();
+        let res = TomlGenerator::with_version(&mut out, TomlVersion::V0_5).write_value(&value);
+        assert!(matches!(res, Err(TomlError::MixedArray(p)) if p == "a"));
+        // nothing is written for invalid documents
+        assert!(out.is_empty());
+
+        let value = object("a", NativeValue::from(vec![1_u64, 2]));
+        let mut out = Vec::new();
+        TomlGenerator::with_version(&mut out, TomlVersion::V0_5)
+            .write_value(&value)
+            .expect("homogeneous array");
+        assert_eq!(out, b"a = [1, 2]\n");
+    }
+}
@@ -0,0 +1,368 @@
+use std::borrow::Borrow;
+use std::io::{self, Write};
+
+use crate::{
+    array::Array,
+    base::{TypedValue, ValueAsContainer, ValueAsScalar},
+    generator::{BaseGenerator, Node},
+    object::Object,
+};
+
+/// Number of spaces per indentation level
+const INDENT: usize = 2;
+
+/// YAML 1.2 block style generator writing to a Writer
+pub struct YamlGenerator<'w, W>
+where
+    W: 'w + Write,
+{
+    writer: &'w mut W,
+}
+
+impl<'w, W> YamlGenerator<'w, W>
+where
+    W: 'w + Write,
+{
+    /// Creates a new generator
+    pub fn new(writer: &'w mut W) -> Self {
+        YamlGenerator { writer }
+    }
+
+    /// Writes a value as a YAML document
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error is encountered
+    pub fn write_value<V>(&mut self, value: &V) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        match Node::of(value) {
+            Node::Array(array) if !array.is_empty() => self.write_sequence::<V>(array, 0, false),
+            Node::Object(object) if !object.is_empty() => self.write_mapping::<V>(object, 0, false),
+            node => self.write_scalar(node, 0),
+        }
+    }
+
+    fn write_indent(&mut self, indent: usize) -> io::Result<()> {
+        for _ in 0..indent {
+            self.write_char(b' ')?;
+        }
+        Ok(())
+    }
+
+    /// Writes the entries of a sequence, if `inline` is set the first entry
+    /// continues the current line (`- - a`)
+    fn write_sequence<V>(&mut self, array: &V::Array, indent: usize, inline: bool) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        for (i, item) in array.iter().enumerate() {
+            if i > 0 || !inline {
+                self.write_indent(indent)?;
+            }
+            self.write(b"- ")?;
+            match Node::of(item) {
+                Node::Array(array) if !array.is_empty() => {
+                    self.write_sequence::<V>(array, indent + INDENT, true)?;
+                }
+                Node::Object(object) if !object.is_empty() => {
+                    self.write_mapping::<V>(object, indent + INDENT, true)?;
+                }
+                node => self.write_scalar(node, indent + INDENT)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the entries of a mapping, if `inline` is set the first entry
+    /// continues the current line (`- a: 1`)
+    fn write_mapping<V>(
+        &mut self,
+        object: &V::Object,
+        indent: usize,
+        inline: bool,
+    ) -> io::Result<()>
+    where
+        V: TypedValue + ValueAsScalar + ValueAsContainer,
+        V::Array: Array<Element = V>,
+        V::Object: Object<Element = V>,
+        <V::Object as Object>::Key: Borrow<str>,
+    {
+        for (i, (k, v)) in object.iter().enumerate() {
+            if i > 0 || !inline {
+                self.write_indent(indent)?;
+            }
+            self.write_key(k.borrow())?;
+            match Node::of(v) {
+                Node::Array(array) if !array.is_empty() => {
+                    self.write(b":\n")?;
+                    self.write_sequence::<V>(array, indent + INDENT, false)?;
+                }
+                Node::Object(object) if !object.is_empty() => {
+                    self.write(b":\n")?;
+                    self.write_mapping::<V>(object, indent + INDENT, false)?;
+                }
+                node => {
+                    self.write(b": ")?;
+                    self.write_scalar(node, indent + INDENT)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_key(&mut self, key: &str) -> io::Result<()> {
+        if is_plain_safe(key) {
+            self.write(key.as_bytes())
+        } else {
+            self.write_quoted(key)
+        }
+    }
+
+    /// Writes a scalar (or empty collection) and terminates the line, block
+    /// scalars have their content indented by `indent`
+    fn write_scalar<V>(&mut self, node: Node<V>, indent: usize) -> io::Result<()>
+    where
+        V: ValueAsContainer,
+        V::Array: Array,
+        V::Object: Object,
+    {
+        match node {
+            Node::Null => self.write(b"null")?,
+            Node::Bool(true) => self.write(b"true")?,
+            Node::Bool(false) => self.write(b"false")?,
+            Node::I64(n) => self.write_int(n)?,
+            Node::U64(n) => self.write_int(n)?,
+            Node::I128(n) => self.write_int(n)?,
+            Node::U128(n) => self.write_int(n)?,
+            Node::F64(n) => self.write_float(n)?,
+            Node::String(s) if is_plain_safe(s) => self.write(s.as_bytes())?,
+            Node::String(s) if is_block_safe(s) => return self.write_block(s, indent),
+            Node::String(s) => self.write_quoted(s)?,
+            Node::Array(_) => self.write(b"[]")?,
+            Node::Object(_) => self.write(b"{}")?,
+        }
+        self.write_char(b'\n')
+    }
+
+    /// Writes a multi line string as a literal block scalar
+    fn write_block(&mut self, s: &str, indent: usize) -> io::Result<()> {
+        let content = s.trim_end_matches('\n');
+        let trailing = s.len() - content.len();
+        // the chomping indicator preserves the exact number of trailing new lines
+        match trailing {
+            0 => self.write(b"|-\n")?,
+            1 => self.write(b"|\n")?,
+            _ => self.write(b"|+\n")?,
+        }
+        for line in content.split('\n') {
+            if !line.is_empty() {
+                self.write_indent(indent)?;
+                self.write(line.as_bytes())?;
+            }
+            self.write_char(b'\n')?;
+        }
+        for _ in 1..trailing {
+            self.write_char(b'\n')?;
+        }
+        Ok(())
+    }
+
+    /// Writes a double quoted string, YAML double quoted strings are a
+    /// superset of JSON strings so we can reuse the JSON escaping, only
+    /// characters YAML considers non printable need extra care.
+    fn write_quoted(&mut self, s: &str) -> io::Result<()> {
+        if !s.chars().any(is_non_printable) {
+            return self.write_string(s);
+        }
+        self.write_char(b'"')?;
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            if is_non_printable(c) {
+                self.write_string_content(&s[start..i])?;
+                write!(self.writer, "\\u{:04x}", u32::from(c))?;
+                start = i + c.len_utf8();
+            }
+        }
+        self.write_string_content(&s[start..])?;
+        self.write_char(b'"')
+    }
+}
+
+impl<'w, W> BaseGenerator for YamlGenerator<'w, W>
+where
+    W: 'w + Write,
+{
+    type T = W;
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut W {
+        self.writer
+    }
+
+    #[inline]
+    fn write_min(&mut self, _: &[u8], min: u8) -> io::Result<()> {
+        self.writer.write_all(&[min])
+    }
+
+    fn write_float(&mut self, num: f64) -> io::Result<()> {
+        if num.is_nan() {
+            self.write(b".nan")
+        } else if num.is_infinite() {
+            self.write(if num > 0.0 { b".inf" } else { b"-.inf" })
+        } else {
+            let mut buffer = ryu::Buffer::new();
+            self.write(buffer.format_finite(num).as_bytes())
+        }
+    }
+}
+
+/// Characters that are neither printable in YAML nor escaped by the JSON
+/// escaping (which already handles the C0 control characters)
+fn is_non_printable(c: char) -> bool {
+    matches!(c, '\u{7f}'..='\u{84}' | '\u{86}'..='\u{9f}' | '\u{fffe}' | '\u{ffff}')
+        || c == '\u{feff}'
+}
+
+/// Checks if a string can be written as a plain (unquoted) scalar without
+/// being read back as something else. This errs on the side of quoting.
+fn is_plain_safe(s: &str) -> bool {
+    // Words that YAML 1.1 or 1.2 parsers resolve to null or booleans
+    const RESERVED: [&str; 11] = [
+        "null", "~", "true", "false", "yes", "no", "on", "off", "y", "n", "",
+    ];
+    let Some(first) = s.bytes().next() else {
+        return false;
+    };
+    // indicators, numbers and things that might look like numbers
+    if b"-?:,[]{}#&*!|>'\"%@`.+ ".contains(&first) || first.is_ascii_digit() {
+        return false;
+    }
+    if RESERVED.iter().any(|r| r.eq_ignore_ascii_case(s)) {
+        return false;
+    }
+    !(s.ends_with(' ')
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.chars().any(|c| c.is_control() || is_non_printable(c)))
+}
+
+/// Checks if a string can be written as a literal block scalar, that is
+/// it spans multiple lines and all content is printable. Strings with
+/// leading white space would need an explicit indentation indicator so
+/// they are quoted instead.
+fn is_block_safe(s: &str) -> bool {
+    s.contains('\n')
+        && !s.trim_start_matches('\n').starts_with(' ')
+        && !s.trim_end_matches('\n').is_empty()
+        && s.chars()
+            .all(|c| c == '\n' || c == '\t' || !(c.is_control() || is_non_printable(c)))
+}
+
+/// Encodes a value as a YAML document
+#[must_use]
+pub fn to_string<V>(value: &V) -> String
+where
+    V: TypedValue + ValueAsScalar + ValueAsContainer,
+    V::Array: Array<Element = V>,
+    V::Object: Object<Element = V>,
+    <V::Object as Object>::Key: Borrow<str>,
+{
+    let mut out = Vec::new();
+    // writing to a Vec can not fail
+    let _: io::Result<()> = YamlGenerator::new(&mut out).write_value(value);
+    // Original strings were unicode, numbers and escapes are all ASCII,
+    // therefore this is safe.
+    unsafe { String::from_utf8_unchecked(out) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_string;
+    use crate::prelude::*;
+    use crate::NativeValue;
+    use std::collections::HashMap;
+
+    fn object(k: &str, v: NativeValue) -> NativeValue {
+        let mut map = HashMap::new();
+        map.insert(k.to_string(), v);
+        NativeValue::from(map)
+    }
+
+    #[test]
+    fn scalars() {
+        for (value, expected) in [
+            (NativeValue::null(), "null\n"),
+            (NativeValue::from(false), "false\n"),
+            (NativeValue::from(-7_i64), "-7\n"),
+            (NativeValue::from(1.5), "1.5\n"),
+            (NativeValue::from(f64::NAN), ".nan\n"),
+            (NativeValue::from(f64::NEG_INFINITY), "-.inf\n"),
+            (NativeValue::from("plain text"), "plain text\n"),
+            (NativeValue::from(Vec::<u64>::new()), "[]\n"),
+            (
+                NativeValue::from(HashMap::<String, NativeValue>::new()),
+                "{}\n",
+            ),
+        ] {
+            assert_eq!(to_string(&value), expected);
+        }
+    }
+
+    #[test]
+    fn ambiguous_strings_are_quoted() {
+        for s in [
+            "", "yes", "No", "NULL", "~", "12", "1.5", "-x", ".5", "a: b", "a #b", "x:", "t ",
+            "#c", "*a", "\t",
+        ] {
+            let expected = format!("{}\n", NativeValue::from(s).encode());
+            assert_eq!(to_string(&NativeValue::from(s)), expected, "{s:?}");
+        }
+        assert_eq!(
+            to_string(&NativeValue::from("\u{80}x\u{7f}")),
+            "\"\\u0080x\\u007f\"\n"
+        );
+    }
+
+    #[test]
+    fn nesting() {
+        let value = object(
+            "list",
+            NativeValue::from(vec![
+                NativeValue::from(vec![1_u64, 2]),
+                object("k", NativeValue::from("v")),
+                object("deep", NativeValue::from(vec![true])),
+                NativeValue::from(Vec::<u64>::new()),
+            ]),
+        );
+        assert_eq!(
+            to_string(&value),
+            "list:\n  - - 1\n    - 2\n  - k: v\n  - deep:\n      - true\n  - []\n"
+        );
+        let value = object("a", object("b", NativeValue::null()));
+        assert_eq!(to_string(&value), "a:\n  b: null\n");
+        let value = object("key: x", NativeValue::from(1_u64));
+        assert_eq!(to_string(&value), "\"key: x\": 1\n");
+    }
+
+    #[test]
+    fn block_scalars() {
+        let value = object("text", NativeValue::from("one\n\ntwo"));
+        assert_eq!(to_string(&value), "text: |-\n  one\n\n  two\n");
+        let value = object("text", NativeValue::from("one\n"));
+        assert_eq!(to_string(&value), "text: |\n  one\n");
+        let value = object("text", NativeValue::from("one\n\n"));
+        assert_eq!(to_string(&value), "text: |+\n  one\n\n");
+        // leading spaces would need an indentation indicator
+        let value = NativeValue::from(" one\ntwo");
+        assert_eq!(to_string(&value), "\" one\\ntwo\"\n");
+    }
+}